@@ -0,0 +1,123 @@
+//! Keywords that describe a schema without restricting the instance
+
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
+use crate::property::Property;
+
+/// The [meta-data annotations](https://json-schema.org/draft/2020-12/json-schema-validation.html#section-9),
+/// the [content annotations](https://json-schema.org/draft/2020-12/json-schema-validation.html#section-8)
+/// and `$comment`, which every subschema may carry
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Annotations {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// `Some(Value::Null)` if the default is `null`
+    #[serde(
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default: Option<Value>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub examples: Option<Vec<Value>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_only: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub write_only: Option<bool>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+
+    #[serde(rename = "$comment", skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    /// How a string encodes binary data, e.g. `base64`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_encoding: Option<String>,
+
+    /// The media type of the (decoded) content of a string, e.g. `application/json`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_media_type: Option<String>,
+
+    /// Describes the content of a string, if it is `application/json`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_schema: Option<Box<Property>>,
+}
+
+/// Keeps an explicit `null`, which `Option<Value>` would otherwise treat as missing
+pub(crate) fn present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// Annotations collected by [`Schema::annotate`](crate::Schema::annotate), keyed by the JSON Pointer
+/// of the instance location they apply to. The root of the instance is `""`.
+pub type AnnotationMap = BTreeMap<String, LocationAnnotations>;
+
+/// Everything the schema said about a single location of a valid instance
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LocationAnnotations {
+    /// The annotations of every subschema that applied to this location, outermost first
+    pub schemas: Vec<Annotations>,
+    /// Members of this object that were validated by `properties`
+    pub evaluated_properties: Vec<String>,
+    /// Elements of this array that were validated by `items`
+    pub evaluated_items: Vec<usize>,
+}
+
+impl LocationAnnotations {
+    pub fn title(&self) -> Option<&str> {
+        self.schemas.iter().find_map(|a| a.title.as_deref())
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.schemas.iter().find_map(|a| a.description.as_deref())
+    }
+
+    pub fn default(&self) -> Option<&Value> {
+        self.schemas.iter().find_map(|a| a.default.as_ref())
+    }
+
+    pub fn examples(&self) -> impl Iterator<Item = &Value> {
+        self.schemas
+            .iter()
+            .flat_map(|a| a.examples.iter().flatten())
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.schemas.iter().any(|a| a.read_only == Some(true))
+    }
+
+    pub fn write_only(&self) -> bool {
+        self.schemas.iter().any(|a| a.write_only == Some(true))
+    }
+
+    pub fn deprecated(&self) -> bool {
+        self.schemas.iter().any(|a| a.deprecated == Some(true))
+    }
+
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.schemas
+            .iter()
+            .find_map(|a| a.content_encoding.as_deref())
+    }
+
+    pub fn content_media_type(&self) -> Option<&str> {
+        self.schemas
+            .iter()
+            .find_map(|a| a.content_media_type.as_deref())
+    }
+}
@@ -0,0 +1,247 @@
+//! Keywords that apply subschemas to the same instance location, regardless of its type
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::draft::Draft;
+use crate::property::Property;
+use crate::validation::{Context, Outcome, Report};
+
+/// [In-place applicators](https://json-schema.org/draft/2020-12/json-schema-core.html#section-10.2)
+/// and the [`unevaluated*`](https://json-schema.org/draft/2020-12/json-schema-core.html#section-11)
+/// keywords, which may appear next to any `type`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Applicators {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_of: Option<Vec<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub any_of: Option<Vec<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub one_of: Option<Vec<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub not: Option<Box<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#if: Option<Box<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub then: Option<Box<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub r#else: Option<Box<Property>>,
+
+    /// Applies to all members of an object that no other keyword evaluated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unevaluated_properties: Option<Box<Property>>,
+
+    /// Applies to all elements of an array that no other keyword evaluated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unevaluated_items: Option<Box<Property>>,
+}
+
+pub(crate) static NO_APPLICATORS: Applicators = Applicators {
+    all_of: None,
+    any_of: None,
+    one_of: None,
+    not: None,
+    r#if: None,
+    then: None,
+    r#else: None,
+    unevaluated_properties: None,
+    unevaluated_items: None,
+};
+
+impl Applicators {
+    /// The subschemas of these keywords, with the keyword and index they are found under
+    pub(crate) fn subschemas(&self) -> Vec<(&'static str, Option<String>, &Property)> {
+        let mut subschemas = Vec::new();
+        for (keyword, list) in [
+            ("allOf", &self.all_of),
+            ("anyOf", &self.any_of),
+            ("oneOf", &self.one_of),
+        ] {
+            for (index, subschema) in list.iter().flatten().enumerate() {
+                subschemas.push((keyword, Some(index.to_string()), subschema));
+            }
+        }
+        for (keyword, single) in [
+            ("not", &self.not),
+            ("if", &self.r#if),
+            ("then", &self.then),
+            ("else", &self.r#else),
+            ("unevaluatedProperties", &self.unevaluated_properties),
+            ("unevaluatedItems", &self.unevaluated_items),
+        ] {
+            if let Some(subschema) = single {
+                subschemas.push((keyword, None, &**subschema));
+            }
+        }
+        subschemas
+    }
+
+    pub(crate) fn subschemas_mut(&mut self) -> Vec<(&'static str, Option<String>, &mut Property)> {
+        let mut subschemas = Vec::new();
+        for (keyword, list) in [
+            ("allOf", &mut self.all_of),
+            ("anyOf", &mut self.any_of),
+            ("oneOf", &mut self.one_of),
+        ] {
+            for (index, subschema) in list.iter_mut().flatten().enumerate() {
+                subschemas.push((keyword, Some(index.to_string()), subschema));
+            }
+        }
+        for (keyword, single) in [
+            ("not", &mut self.not),
+            ("if", &mut self.r#if),
+            ("then", &mut self.then),
+            ("else", &mut self.r#else),
+            ("unevaluatedProperties", &mut self.unevaluated_properties),
+            ("unevaluatedItems", &mut self.unevaluated_items),
+        ] {
+            if let Some(subschema) = single {
+                subschemas.push((keyword, None, &mut **subschema));
+            }
+        }
+        subschemas
+    }
+
+    /// Applies these keywords on top of the `outcome` of the other keywords of the same subschema
+    pub(crate) fn apply(&self, json: &Value, context: &mut Context, outcome: Outcome) -> Outcome {
+        let mut report = Report::default();
+        report.add(outcome);
+
+        for subschema in self.all_of.iter().flatten() {
+            if context.done() {
+                break;
+            }
+            report.add(subschema.validate_in(json, context));
+        }
+
+        if let Some(any_of) = &self.any_of {
+            let mut matched = false;
+            for subschema in any_of {
+                if let Ok(evaluated) =
+                    context.attempt(|context| subschema.validate_in(json, context))
+                {
+                    matched = true;
+                    report.evaluated.merge(evaluated);
+                }
+            }
+            if !matched {
+                report.error(
+                    context.error(format!("{:?} does not match any subschema of anyOf", json)),
+                );
+            }
+        }
+
+        if let Some(one_of) = &self.one_of {
+            let mut matches: Vec<_> = one_of
+                .iter()
+                .filter_map(|subschema| {
+                    context
+                        .attempt(|context| subschema.validate_in(json, context))
+                        .ok()
+                })
+                .collect();
+            match matches.len() {
+                1 => report.add(Ok(matches.remove(0))),
+                0 => report.error(
+                    context.error(format!("{:?} does not match any subschema of oneOf", json)),
+                ),
+                n => report.error(context.error(format!(
+                    "{:?} matches {} subschemas of oneOf instead of exactly one",
+                    json, n
+                ))),
+            }
+        }
+
+        if let Some(not) = &self.not {
+            if context
+                .discarding(|context| not.validate_in(json, context))
+                .is_ok()
+            {
+                report.error(
+                    context.error(format!("{:?} must not match the subschema of not", json)),
+                );
+            }
+        }
+
+        if let Some(condition) = &self.r#if {
+            match context.attempt(|context| condition.validate_in(json, context)) {
+                Ok(evaluated) => {
+                    report.evaluated.merge(evaluated);
+                    if let Some(then) = &self.then {
+                        report.add(then.validate_in(json, context));
+                    }
+                }
+                Err(_) => {
+                    if let Some(otherwise) = &self.r#else {
+                        report.add(otherwise.validate_in(json, context));
+                    }
+                }
+            }
+        }
+
+        // everything else has to pass first, only then is it known what was evaluated
+        if report.errors.is_empty() && context.draft_at_least(Draft::Draft201909) {
+            self.apply_unevaluated(json, context, &mut report);
+        }
+        report.into_outcome()
+    }
+
+    fn apply_unevaluated(&self, json: &Value, context: &mut Context, report: &mut Report) {
+        match (json, &self.unevaluated_properties, &self.unevaluated_items) {
+            (Value::Object(object), Some(subschema), _) => {
+                for (name, value) in object {
+                    if report.evaluated.properties.contains(name) {
+                        continue;
+                    }
+                    if context.done() {
+                        break;
+                    }
+                    if subschema.is_false() {
+                        report.error(
+                            context
+                                .error(format!("unevaluated property {:?} is not allowed", name)),
+                        );
+                    } else {
+                        match context.descend(name, |context| subschema.validate_in(value, context))
+                        {
+                            Ok(_) => context.evaluated_property(name),
+                            Err(errors) => report.errors.extend(errors),
+                        }
+                    }
+                    report.evaluated.properties.insert(name.clone());
+                }
+            }
+            (Value::Array(elements), _, Some(subschema)) => {
+                for (index, value) in elements.iter().enumerate() {
+                    if report.evaluated.items.contains(&index) {
+                        continue;
+                    }
+                    if context.done() {
+                        break;
+                    }
+                    if subschema.is_false() {
+                        report.error(
+                            context.error(format!("unevaluated item {} is not allowed", index)),
+                        );
+                    } else {
+                        match context
+                            .descend(index, |context| subschema.validate_in(value, context))
+                        {
+                            Ok(_) => context.evaluated_item(index),
+                            Err(errors) => report.errors.extend(errors),
+                        }
+                    }
+                    report.evaluated.items.insert(index);
+                }
+            }
+            _ => {}
+        }
+    }
+}
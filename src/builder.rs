@@ -0,0 +1,744 @@
+//! Assembling schemas in code instead of parsing them
+//!
+//! Each keyword ends up where parsing the same document would put it, so it is validated the same
+//! way. Keywords of another `type`, e.g. `minLength` on an integer, are kept but have no effect.
+//!
+//! ```
+//! use serde_json::json;
+//! use serde_json_schema::Schema;
+//!
+//! let schema = Schema::object()
+//!     .title("User")
+//!     .property("id", Schema::integer().minimum(1))
+//!     .property("name", Schema::string())
+//!     .required(["id"])
+//!     .additional_properties(false)
+//!     .build();
+//!
+//! assert!(schema.validate(&json!({"id": 1, "name": "Hoodie"})).is_ok());
+//! assert!(schema.validate(&json!({"name": "Hoodie"})).is_err());
+//! assert!(schema.validate(&json!({"id": 0})).is_err());
+//! ```
+
+use serde_json::{Map, Value};
+use url::Url;
+
+use crate::{
+    annotation::Annotations,
+    applicator::Applicators,
+    draft::Draft,
+    id::SchemaId,
+    property::{AnyProperty, Items, Property, PropertyInstance, RefProperty, UntypedKeywords},
+    validation::{ArrayCriteria, NumberCriteria, StringCriteria, ValueCriteria},
+    Schema, SchemaDefinition, SchemaInner, SchemaMap,
+};
+
+/// Builds a [`Schema`] or a subschema keyword by keyword, started by e.g. [`Schema::object`].
+///
+/// Keywords that belong to another `type`, e.g. `required` on a string, are kept next to the
+/// others just like in a parsed document, where they are ignored during validation as well.
+#[derive(Debug, Clone)]
+pub struct SchemaBuilder {
+    id: Option<SchemaId>,
+    schema: Option<Url>,
+    dependencies: Option<SchemaMap<Vec<String>>>,
+    definitions: Option<SchemaMap<SchemaDefinition>>,
+    property: Property,
+}
+
+impl Schema {
+    /// Starts a schema with `"type": "null"`
+    pub fn null() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::Null {
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema with `"type": "boolean"`
+    pub fn boolean() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::Boolean {
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema with `"type": "integer"`
+    pub fn integer() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::Integer {
+            criteria: NumberCriteria::default(),
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema with `"type": "number"`
+    pub fn number() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::Number {
+            criteria: NumberCriteria::default(),
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema with `"type": "string"`
+    pub fn string() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::String {
+            format: None,
+            criteria: StringCriteria::default(),
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema with `"type": "object"`
+    pub fn object() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::Object {
+            properties: SchemaMap::default(),
+            required: None,
+            pattern_properties: None,
+            additional_properties: None,
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema with `"type": "array"` whose `items` accept anything
+    pub fn array() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Value(PropertyInstance::Array {
+            items: Box::new(Property::Boolean(true)),
+            criteria: ArrayCriteria::default(),
+            values: ValueCriteria::default(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            extra: Map::new(),
+        }))
+    }
+
+    /// Starts a schema without a `type`, i.e. `{}`
+    pub fn any() -> SchemaBuilder {
+        SchemaBuilder::from(Property::Any(AnyProperty::default()))
+    }
+
+    /// Starts a schema that consists of a `$ref`
+    pub fn reference(reference: impl Into<String>) -> SchemaBuilder {
+        SchemaBuilder::from(Property::Ref(RefProperty {
+            reference: reference.into(),
+            annotations: Annotations::default(),
+            applicators: Applicators::default(),
+            keywords: UntypedKeywords::default(),
+            format: None,
+            number: NumberCriteria::default(),
+            string: StringCriteria::default(),
+            array: ArrayCriteria::default(),
+            values: ValueCriteria::default(),
+            extra: Map::new(),
+        }))
+    }
+}
+
+impl SchemaBuilder {
+    /// The root schema as a document.
+    ///
+    /// Definitions end up under `$defs` if the declared [`meta_schema`](SchemaBuilder::meta_schema)
+    /// is 2019-09 or later, under `definitions` otherwise.
+    pub fn build(self) -> Schema {
+        let definition = self.into_definition();
+        match definition.as_boolean() {
+            Some(value) => Schema(SchemaInner::Boolean(value)),
+            None => Schema(SchemaInner::Schema(definition)),
+        }
+    }
+
+    fn into_definition(self) -> SchemaDefinition {
+        let modern = self
+            .schema
+            .as_ref()
+            .and_then(Draft::from_url)
+            .is_some_and(|draft| draft >= Draft::Draft201909);
+        let (definitions, defs) = match modern {
+            true => (None, self.definitions),
+            false => (self.definitions, None),
+        };
+        SchemaDefinition {
+            id: self.id,
+            schema: self.schema,
+            dependencies: self.dependencies,
+            specification: Some(self.property),
+            definitions,
+            defs,
+        }
+    }
+
+    /// The keywords every subschema may carry, `true` and `false` are turned into `{}` and `{"not": {}}`
+    fn parts(&mut self) -> (&mut Annotations, &mut Applicators, &mut Map<String, Value>) {
+        self.property.expand_boolean();
+        self.property
+            .parts_mut()
+            .expect("only boolean schemas have no keywords")
+    }
+
+    fn annotations(&mut self) -> &mut Annotations {
+        self.parts().0
+    }
+
+    fn applicators(&mut self) -> &mut Applicators {
+        self.parts().1
+    }
+
+    /// The keywords of the types, if there is no single `type`
+    fn untyped(&mut self) -> Option<&mut UntypedKeywords> {
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Any(AnyProperty { keywords, .. })
+            | Property::Ref(RefProperty { keywords, .. }) => Some(keywords),
+            _ => None,
+        }
+    }
+
+    fn number_criteria(&mut self) -> Option<&mut NumberCriteria> {
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::Integer { criteria, .. })
+            | Property::Value(PropertyInstance::Number { criteria, .. }) => Some(criteria),
+            Property::Any(AnyProperty { number, .. })
+            | Property::Ref(RefProperty { number, .. }) => Some(number),
+            _ => None,
+        }
+    }
+
+    fn string_criteria(&mut self) -> Option<&mut StringCriteria> {
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::String { criteria, .. }) => Some(criteria),
+            Property::Any(AnyProperty { string, .. })
+            | Property::Ref(RefProperty { string, .. }) => Some(string),
+            _ => None,
+        }
+    }
+
+    fn array_criteria(&mut self) -> Option<&mut ArrayCriteria> {
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::Array { criteria, .. }) => Some(criteria),
+            Property::Any(AnyProperty { array, .. }) | Property::Ref(RefProperty { array, .. }) => {
+                Some(array)
+            }
+            _ => None,
+        }
+    }
+
+    fn value_criteria(&mut self) -> &mut ValueCriteria {
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(instance) => instance.values_mut(),
+            Property::Any(AnyProperty { values, .. })
+            | Property::Ref(RefProperty { values, .. }) => values,
+            Property::Boolean(_) => unreachable!("booleans were expanded"),
+        }
+    }
+
+    /// Sets a keyword that this crate does not model, such as an `x-*` extension
+    pub fn keyword(mut self, name: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.parts().2.insert(name.into(), value.into());
+        self
+    }
+
+    /// Adds `key` to the object of a keyword that this crate does not model for this `type`
+    fn keyword_entry(mut self, name: &str, key: impl Into<String>, value: Value) -> Self {
+        let extra = self.parts().2;
+        let entry = extra
+            .entry(name)
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Value::Object(object) = entry {
+            object.insert(key.into(), value);
+        }
+        self
+    }
+
+    // document
+
+    /// Sets `$id`, only kept by [`build`](SchemaBuilder::build)
+    pub fn id(mut self, id: impl Into<SchemaId>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Sets `$schema` to the meta-schema of `draft`, only kept by [`build`](SchemaBuilder::build)
+    pub fn meta_schema(mut self, draft: Draft) -> Self {
+        self.schema = Some(draft.url());
+        self
+    }
+
+    /// Adds a definition, only kept by [`build`](SchemaBuilder::build)
+    pub fn definition(
+        mut self,
+        name: impl Into<String>,
+        definition: impl Into<SchemaBuilder>,
+    ) -> Self {
+        self.definitions
+            .get_or_insert_with(SchemaMap::default)
+            .insert(name.into(), definition.into().into_definition());
+        self
+    }
+
+    /// Adds to the `dependencies` of the document, only kept by [`build`](SchemaBuilder::build)
+    pub fn dependency<I, S>(mut self, name: impl Into<String>, requires: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.dependencies
+            .get_or_insert_with(SchemaMap::default)
+            .insert(name.into(), requires.into_iter().map(Into::into).collect());
+        self
+    }
+
+    // annotations
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.annotations().title = Some(title.into());
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.annotations().description = Some(description.into());
+        self
+    }
+
+    pub fn default(mut self, default: impl Into<Value>) -> Self {
+        self.annotations().default = Some(default.into());
+        self
+    }
+
+    /// Adds to the `examples`
+    pub fn example(mut self, example: impl Into<Value>) -> Self {
+        self.annotations()
+            .examples
+            .get_or_insert_with(Vec::new)
+            .push(example.into());
+        self
+    }
+
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.annotations().read_only = Some(read_only);
+        self
+    }
+
+    pub fn write_only(mut self, write_only: bool) -> Self {
+        self.annotations().write_only = Some(write_only);
+        self
+    }
+
+    pub fn deprecated(mut self, deprecated: bool) -> Self {
+        self.annotations().deprecated = Some(deprecated);
+        self
+    }
+
+    pub fn comment(mut self, comment: impl Into<String>) -> Self {
+        self.annotations().comment = Some(comment.into());
+        self
+    }
+
+    pub fn content_encoding(mut self, encoding: impl Into<String>) -> Self {
+        self.annotations().content_encoding = Some(encoding.into());
+        self
+    }
+
+    pub fn content_media_type(mut self, media_type: impl Into<String>) -> Self {
+        self.annotations().content_media_type = Some(media_type.into());
+        self
+    }
+
+    pub fn content_schema(mut self, schema: impl Into<Property>) -> Self {
+        self.annotations().content_schema = Some(Box::new(schema.into()));
+        self
+    }
+
+    // applicators
+
+    /// Adds to the `allOf`
+    pub fn all_of(mut self, schema: impl Into<Property>) -> Self {
+        self.applicators()
+            .all_of
+            .get_or_insert_with(Vec::new)
+            .push(schema.into());
+        self
+    }
+
+    /// Adds to the `anyOf`
+    pub fn any_of(mut self, schema: impl Into<Property>) -> Self {
+        self.applicators()
+            .any_of
+            .get_or_insert_with(Vec::new)
+            .push(schema.into());
+        self
+    }
+
+    /// Adds to the `oneOf`
+    pub fn one_of(mut self, schema: impl Into<Property>) -> Self {
+        self.applicators()
+            .one_of
+            .get_or_insert_with(Vec::new)
+            .push(schema.into());
+        self
+    }
+
+    pub fn not(mut self, schema: impl Into<Property>) -> Self {
+        self.applicators().not = Some(Box::new(schema.into()));
+        self
+    }
+
+    /// Sets `if` and `then`
+    pub fn if_then(mut self, condition: impl Into<Property>, then: impl Into<Property>) -> Self {
+        let applicators = self.applicators();
+        applicators.r#if = Some(Box::new(condition.into()));
+        applicators.then = Some(Box::new(then.into()));
+        self
+    }
+
+    /// Sets `if`, `then` and `else`
+    pub fn if_then_else(
+        self,
+        condition: impl Into<Property>,
+        then: impl Into<Property>,
+        otherwise: impl Into<Property>,
+    ) -> Self {
+        let mut builder = self.if_then(condition, then);
+        builder.applicators().r#else = Some(Box::new(otherwise.into()));
+        builder
+    }
+
+    pub fn unevaluated_properties(mut self, schema: impl Into<Property>) -> Self {
+        self.applicators().unevaluated_properties = Some(Box::new(schema.into()));
+        self
+    }
+
+    pub fn unevaluated_items(mut self, schema: impl Into<Property>) -> Self {
+        self.applicators().unevaluated_items = Some(Box::new(schema.into()));
+        self
+    }
+
+    // objects
+
+    /// Adds to the `properties`
+    pub fn property(self, name: impl Into<String>, schema: impl Into<Property>) -> Self {
+        let mut builder = self;
+        let schema = schema.into();
+        if let Property::Value(PropertyInstance::Object { properties, .. }) = &mut builder.property
+        {
+            properties.insert(name.into(), schema);
+            return builder;
+        }
+        if let Some(untyped) = builder.untyped() {
+            untyped
+                .properties
+                .get_or_insert_with(SchemaMap::default)
+                .insert(name.into(), schema);
+            return builder;
+        }
+        builder.keyword_entry("properties", name, to_value(&schema))
+    }
+
+    /// Adds to the `required` properties
+    pub fn required<I, S>(mut self, names: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into);
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::Object { required, .. }) => {
+                required.get_or_insert_with(Vec::new).extend(names);
+                self
+            }
+            Property::Any(AnyProperty { keywords, .. })
+            | Property::Ref(RefProperty { keywords, .. }) => {
+                keywords.required.get_or_insert_with(Vec::new).extend(names);
+                self
+            }
+            _ => {
+                let extra = self.parts().2;
+                let entry = extra
+                    .entry("required")
+                    .or_insert_with(|| Value::Array(Vec::new()));
+                if let Value::Array(required) = entry {
+                    required.extend(names.map(Value::String));
+                }
+                self
+            }
+        }
+    }
+
+    /// Adds to the `patternProperties`
+    pub fn pattern_property(self, pattern: impl Into<String>, schema: impl Into<Property>) -> Self {
+        let mut builder = self;
+        let schema = schema.into();
+        if let Property::Value(PropertyInstance::Object {
+            pattern_properties, ..
+        }) = &mut builder.property
+        {
+            pattern_properties
+                .get_or_insert_with(SchemaMap::default)
+                .insert(pattern.into(), schema);
+            return builder;
+        }
+        if let Some(untyped) = builder.untyped() {
+            untyped
+                .pattern_properties
+                .get_or_insert_with(SchemaMap::default)
+                .insert(pattern.into(), schema);
+            return builder;
+        }
+        builder.keyword_entry("patternProperties", pattern, to_value(&schema))
+    }
+
+    pub fn additional_properties(mut self, schema: impl Into<Property>) -> Self {
+        let schema = schema.into();
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::Object {
+                additional_properties,
+                ..
+            }) => {
+                *additional_properties = Some(Box::new(schema));
+                self
+            }
+            Property::Any(AnyProperty { keywords, .. })
+            | Property::Ref(RefProperty { keywords, .. }) => {
+                keywords.additional_properties = Some(Box::new(schema));
+                self
+            }
+            _ => self.keyword("additionalProperties", to_value(&schema)),
+        }
+    }
+
+    // arrays
+
+    pub fn items(mut self, schema: impl Into<Property>) -> Self {
+        let schema = schema.into();
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::Array { items, .. }) => {
+                **items = schema;
+                self
+            }
+            Property::Any(AnyProperty { keywords, .. })
+            | Property::Ref(RefProperty { keywords, .. }) => {
+                keywords.items = Some(Items::Each(Box::new(schema)));
+                self
+            }
+            _ => self.keyword("items", to_value(&schema)),
+        }
+    }
+
+    pub fn min_items(mut self, min: u64) -> Self {
+        match self.array_criteria() {
+            Some(criteria) => {
+                criteria.min_items = Some(min.into());
+                self
+            }
+            None => self.keyword("minItems", min),
+        }
+    }
+
+    pub fn max_items(mut self, max: u64) -> Self {
+        match self.array_criteria() {
+            Some(criteria) => {
+                criteria.max_items = Some(max.into());
+                self
+            }
+            None => self.keyword("maxItems", max),
+        }
+    }
+
+    // strings
+
+    pub fn format(mut self, format: impl Into<String>) -> Self {
+        let format = format.into();
+        self.property.expand_boolean();
+        match &mut self.property {
+            Property::Value(PropertyInstance::String {
+                format: current, ..
+            })
+            | Property::Any(AnyProperty {
+                format: current, ..
+            })
+            | Property::Ref(RefProperty {
+                format: current, ..
+            }) => {
+                *current = Some(format);
+                self
+            }
+            _ => self.keyword("format", format),
+        }
+    }
+
+    pub fn min_length(mut self, min: u64) -> Self {
+        match self.string_criteria() {
+            Some(criteria) => {
+                criteria.min_length = Some(min.into());
+                self
+            }
+            None => self.keyword("minLength", min),
+        }
+    }
+
+    pub fn max_length(mut self, max: u64) -> Self {
+        match self.string_criteria() {
+            Some(criteria) => {
+                criteria.max_length = Some(max.into());
+                self
+            }
+            None => self.keyword("maxLength", max),
+        }
+    }
+
+    pub fn pattern(mut self, pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        match self.string_criteria() {
+            Some(criteria) => {
+                criteria.pattern = Some(pattern);
+                self
+            }
+            None => self.keyword("pattern", pattern),
+        }
+    }
+
+    // numbers
+
+    pub fn minimum(mut self, minimum: impl Into<Value>) -> Self {
+        let minimum = minimum.into();
+        match self.number_criteria() {
+            Some(criteria) => {
+                criteria.minimum = Some(minimum);
+                self
+            }
+            None => self.keyword("minimum", minimum),
+        }
+    }
+
+    pub fn maximum(mut self, maximum: impl Into<Value>) -> Self {
+        let maximum = maximum.into();
+        match self.number_criteria() {
+            Some(criteria) => {
+                criteria.maximum = Some(maximum);
+                self
+            }
+            None => self.keyword("maximum", maximum),
+        }
+    }
+
+    pub fn exclusive_minimum(mut self, minimum: impl Into<Value>) -> Self {
+        let minimum = minimum.into();
+        match self.number_criteria() {
+            Some(criteria) => {
+                criteria.exclusive_minimum = Some(minimum);
+                self
+            }
+            None => self.keyword("exclusiveMinimum", minimum),
+        }
+    }
+
+    pub fn exclusive_maximum(mut self, maximum: impl Into<Value>) -> Self {
+        let maximum = maximum.into();
+        match self.number_criteria() {
+            Some(criteria) => {
+                criteria.exclusive_maximum = Some(maximum);
+                self
+            }
+            None => self.keyword("exclusiveMaximum", maximum),
+        }
+    }
+
+    pub fn multiple_of(mut self, factor: impl Into<Value>) -> Self {
+        let factor = factor.into();
+        match self.number_criteria() {
+            Some(criteria) => {
+                criteria.multiple_of = Some(factor);
+                self
+            }
+            None => self.keyword("multipleOf", factor),
+        }
+    }
+
+    // any type
+
+    /// Sets `enum`
+    pub fn enum_values<I, V>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = V>,
+        V: Into<Value>,
+    {
+        self.value_criteria().enum_values = Some(values.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets `const`
+    pub fn const_value(mut self, value: impl Into<Value>) -> Self {
+        self.value_criteria().const_value = Some(value.into());
+        self
+    }
+}
+
+fn to_value(property: &Property) -> Value {
+    serde_json::to_value(property).expect("subschemas serialize to json")
+}
+
+impl From<Property> for SchemaBuilder {
+    fn from(property: Property) -> Self {
+        SchemaBuilder {
+            id: None,
+            schema: None,
+            dependencies: None,
+            definitions: None,
+            property,
+        }
+    }
+}
+
+/// `true` or `false`
+impl From<bool> for SchemaBuilder {
+    fn from(value: bool) -> Self {
+        SchemaBuilder::from(Property::Boolean(value))
+    }
+}
+
+/// The builder as a subschema, `$id`, `$schema`, definitions and dependencies are dropped
+impl From<SchemaBuilder> for Property {
+    fn from(builder: SchemaBuilder) -> Self {
+        builder.property
+    }
+}
+
+impl From<bool> for Property {
+    fn from(value: bool) -> Self {
+        Property::Boolean(value)
+    }
+}
+
+impl From<SchemaBuilder> for Schema {
+    fn from(builder: SchemaBuilder) -> Self {
+        builder.build()
+    }
+}
+
+/// The builder as a definition, see [`Schema::definitions_mut`]
+impl From<SchemaBuilder> for SchemaDefinition {
+    fn from(builder: SchemaBuilder) -> Self {
+        builder.into_definition()
+    }
+}
@@ -0,0 +1,192 @@
+//! Embedding external `$ref` targets into a single document
+
+use url::Url;
+
+use crate::draft::Draft;
+use crate::error::{ErrorKind, Result};
+use crate::id::SchemaId;
+use crate::property::RefProperty;
+use crate::resolver::Resolver;
+use crate::{Schema, SchemaDefinition, SchemaInner, SchemaMap};
+
+/// An external document that is being embedded
+struct Resource {
+    /// Where it was retrieved from
+    url: Url,
+    /// Its own `$id`, or `url` if it has none
+    id: Url,
+    name: String,
+}
+
+struct Bundler<'r> {
+    resolver: &'r dyn Resolver,
+    /// Whether references are kept and resolved via `$id`, rather than rewritten to local pointers
+    keep_ids: bool,
+    root: Option<Url>,
+    /// Names already in use in the root's `definitions` or `$defs`
+    taken: Vec<String>,
+    resources: Vec<Resource>,
+    /// Embedded resources whose references have not been rewritten yet
+    pending: Vec<(usize, SchemaDefinition)>,
+}
+
+fn id_url(definition: &SchemaDefinition, retrieved_from: Option<&Url>) -> Option<Url> {
+    match (&definition.id, retrieved_from) {
+        (Some(SchemaId::Url(id)), _) => Some(id.clone()),
+        (Some(id), Some(base)) => base.join(&id.to_string()).ok(),
+        (None, base) => base.cloned(),
+        _ => None,
+    }
+    .map(|mut url| {
+        url.set_fragment(None);
+        url
+    })
+}
+
+fn escape(segment: &str) -> String {
+    segment.replace('~', "~0").replace('/', "~1")
+}
+
+impl Bundler<'_> {
+    /// Reference to `pointer` within the embedded resource `index`
+    fn local(&self, index: usize, pointer: &str) -> Result<String> {
+        let resource = &self.resources[index];
+        if self.keep_ids {
+            let mut url = resource.id.clone();
+            url.set_fragment(Some(pointer).filter(|pointer| !pointer.is_empty()));
+            return Ok(url.to_string());
+        }
+        if !pointer.is_empty() && !pointer.starts_with('/') {
+            // plain name fragments only make sense together with the `$id`s we drop here
+            return Err(ErrorKind::UnresolvableRef(format!("{}#{}", resource.url, pointer)).into());
+        }
+        Ok(format!(
+            "#/definitions/{}{}",
+            escape(&resource.name),
+            pointer
+        ))
+    }
+
+    /// Embeds the document `url` points into, unless it already is, and returns the new reference
+    fn embed(&mut self, mut url: Url) -> Result<String> {
+        let pointer = url.fragment().unwrap_or_default().to_owned();
+        url.set_fragment(None);
+        if self.root.as_ref() == Some(&url) {
+            return Ok(format!("#{}", pointer));
+        }
+
+        let known = self
+            .resources
+            .iter()
+            .position(|resource| resource.url == url || resource.id == url);
+        let index = match known {
+            Some(index) => index,
+            None => {
+                let definition = match self.resolver.resolve(&url)?.0 {
+                    SchemaInner::Schema(definition) => definition,
+                    SchemaInner::Boolean(_) => {
+                        return Err(ErrorKind::UnresolvableRef(url.to_string()).into())
+                    }
+                };
+                let id = id_url(&definition, Some(&url)).unwrap_or_else(|| url.clone());
+                let stem = url
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or("external")
+                    .to_owned();
+                let mut name = stem.clone();
+                let mut counter = 1;
+                while self.taken.contains(&name)
+                    || self.resources.iter().any(|resource| resource.name == name)
+                {
+                    counter += 1;
+                    name = format!("{}-{}", stem, counter);
+                }
+                self.resources.push(Resource { url, id, name });
+                self.pending.push((self.resources.len() - 1, definition));
+                self.resources.len() - 1
+            }
+        };
+        self.local(index, &pointer)
+    }
+
+    /// Rewrites every reference in `definition`, which is the resource `index` or the root
+    fn rewrite(
+        &mut self,
+        definition: &mut SchemaDefinition,
+        index: Option<usize>,
+        base: Option<&Url>,
+    ) -> Result<()> {
+        definition.try_for_each_ref_mut(&mut |reference: &mut RefProperty| {
+            reference.reference = match (reference.reference.strip_prefix('#'), index) {
+                (Some(_), None) => return Ok(()),
+                (Some(pointer), Some(index)) => self.local(index, pointer)?,
+                (None, _) => {
+                    let url = match base {
+                        Some(base) => base.join(&reference.reference),
+                        None => Url::parse(&reference.reference),
+                    }
+                    .map_err(|_| ErrorKind::UnresolvableRef(reference.reference.clone()))?;
+                    self.embed(url)?
+                }
+            };
+            Ok(())
+        })
+    }
+}
+
+pub(crate) fn bundle(schema: &Schema, resolver: &dyn Resolver) -> Result<Schema> {
+    let mut root = match &schema.0 {
+        SchemaInner::Schema(definition) => definition.clone(),
+        SchemaInner::Boolean(_) => return Ok(schema.clone()),
+    };
+    let root_url = id_url(&root, None);
+    // like validation, a document without `$schema` follows the latest draft
+    let keep_ids = schema
+        .draft()
+        .is_none_or(|draft| draft >= Draft::Draft201909);
+
+    let existing = if keep_ids {
+        &root.defs
+    } else {
+        &root.definitions
+    };
+    let mut bundler = Bundler {
+        resolver,
+        keep_ids,
+        root: root_url.clone(),
+        taken: existing.iter().flat_map(SchemaMap::keys).cloned().collect(),
+        resources: Vec::new(),
+        pending: Vec::new(),
+    };
+    bundler.rewrite(&mut root, None, root_url.as_ref())?;
+
+    // rewriting one resource may discover and queue further ones
+    let mut embedded = SchemaMap::new();
+    while !bundler.pending.is_empty() {
+        let (index, mut definition) = bundler.pending.remove(0);
+        let base = bundler.resources[index].id.clone();
+        bundler.rewrite(&mut definition, Some(index), Some(&base))?;
+        if keep_ids {
+            definition.id = Some(SchemaId::Url(base));
+        } else {
+            definition.id = None;
+            definition.schema = None;
+        }
+        embedded.insert(bundler.resources[index].name.clone(), definition);
+    }
+
+    if !embedded.is_empty() {
+        let definitions = if keep_ids {
+            &mut root.defs
+        } else {
+            &mut root.definitions
+        };
+        definitions
+            .get_or_insert_with(SchemaMap::new)
+            .extend(embedded);
+    }
+
+    Ok(Schema(SchemaInner::Schema(root)))
+}
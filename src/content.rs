@@ -0,0 +1,66 @@
+//! Decoding the content of strings, as described by `contentEncoding` and `contentMediaType`
+
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use serde_json::Value;
+
+const PADDING_OPTIONAL: GeneralPurposeConfig =
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent);
+const BASE64: GeneralPurpose = GeneralPurpose::new(&alphabet::STANDARD, PADDING_OPTIONAL);
+const BASE64_URL: GeneralPurpose = GeneralPurpose::new(&alphabet::URL_SAFE, PADDING_OPTIONAL);
+
+/// Decodes `content` according to its `contentEncoding`
+pub(crate) fn decode(encoding: &str, content: &str) -> Result<Vec<u8>, String> {
+    match encoding.to_ascii_lowercase().as_str() {
+        "base64" => BASE64.decode(content).map_err(|e| e.to_string()),
+        "base64url" => BASE64_URL.decode(content).map_err(|e| e.to_string()),
+        "quoted-printable" => decode_quoted_printable(content),
+        "7bit" | "8bit" | "binary" => Ok(content.as_bytes().to_vec()),
+        _ => Err(format!("unknown content encoding {:?}", encoding)),
+    }
+}
+
+/// [RFC 2045](https://tools.ietf.org/html/rfc2045#section-6.7), including soft line breaks
+fn decode_quoted_printable(content: &str) -> Result<Vec<u8>, String> {
+    let mut decoded = Vec::with_capacity(content.len());
+    let mut bytes = content.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'=' {
+            decoded.push(byte);
+            continue;
+        }
+        match (bytes.next(), bytes.next()) {
+            (Some(b'\r'), Some(b'\n')) => {}
+            (Some(b'\n'), Some(next)) => decoded.push(next),
+            (Some(b'\n'), None) => {}
+            (Some(high), Some(low)) => {
+                let hex = [high, low];
+                let hex = std::str::from_utf8(&hex).map_err(|e| e.to_string())?;
+                let byte = u8::from_str_radix(hex, 16)
+                    .map_err(|_| format!("invalid escape sequence \"={}\"", hex))?;
+                decoded.push(byte);
+            }
+            _ => return Err(String::from("incomplete escape sequence")),
+        }
+    }
+    Ok(decoded)
+}
+
+/// Whether `media_type` is JSON, e.g. `application/json` or `application/schema+json`
+pub(crate) fn is_json(media_type: &str) -> bool {
+    let essence = media_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    essence == "application/json" || essence.ends_with("+json")
+}
+
+/// Parses decoded JSON content
+pub(crate) fn parse_json(content: &[u8]) -> Result<Value, String> {
+    serde_json::from_slice(content).map_err(|e| e.to_string())
+}
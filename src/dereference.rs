@@ -0,0 +1,111 @@
+//! Inlining `$ref` targets
+
+use std::ptr;
+
+use crate::applicator::Applicators;
+use crate::draft::Draft;
+use crate::error::{ErrorKind, Result};
+use crate::property::{AnyProperty, Property, RecursiveRefs};
+use crate::{Schema, SchemaDefinition, SchemaInner};
+
+struct Inliner<'a> {
+    schema: &'a Schema,
+    recursive: RecursiveRefs,
+    /// Whether keywords next to `$ref` apply, which they do since 2019-09
+    siblings: bool,
+    /// The subschemas that are currently being inlined, a reference to one of them is recursive
+    ancestors: Vec<&'a Property>,
+}
+
+impl<'a> Inliner<'a> {
+    fn property(&mut self, original: &'a Property) -> Result<Property> {
+        if let Property::Ref(reference) = original {
+            let target = reference.resolve(self.schema)?;
+            if self
+                .ancestors
+                .iter()
+                .any(|ancestor| ptr::eq(*ancestor, target))
+            {
+                return match self.recursive {
+                    RecursiveRefs::Keep => Ok(original.clone()),
+                    RecursiveRefs::Error => {
+                        Err(ErrorKind::RecursiveRef(reference.reference.clone()).into())
+                    }
+                };
+            }
+            let inlined = self.property(target)?;
+            let siblings = AnyProperty {
+                annotations: reference.annotations.clone(),
+                applicators: reference.applicators.clone(),
+                keywords: reference.keywords.clone(),
+                format: reference.format.clone(),
+                number: reference.number.clone(),
+                string: reference.string.clone(),
+                array: reference.array.clone(),
+                values: reference.values.clone(),
+                extra: reference.extra.clone(),
+            };
+            if !self.siblings || siblings == AnyProperty::default() {
+                return Ok(inlined);
+            }
+            // the keywords next to `$ref` apply to the same instance as its target
+            let siblings = self.children(original, Property::Any(siblings))?;
+            return Ok(Property::Any(AnyProperty {
+                applicators: Applicators {
+                    all_of: Some(vec![inlined, siblings]),
+                    ..Applicators::default()
+                },
+                ..AnyProperty::default()
+            }));
+        }
+
+        self.children(original, original.clone())
+    }
+
+    /// Inlines the references below `original` into `copy`, which has the same subschemas
+    fn children(&mut self, original: &'a Property, mut copy: Property) -> Result<Property> {
+        self.ancestors.push(original);
+        for ((_, _, original), (_, _, subschema)) in
+            original.subschemas().into_iter().zip(copy.subschemas_mut())
+        {
+            *subschema = self.property(original)?;
+        }
+        self.ancestors.pop();
+        Ok(copy)
+    }
+
+    fn definition(&mut self, original: &'a SchemaDefinition) -> Result<SchemaDefinition> {
+        let mut copy = original.clone();
+        if let Some(specification) = &original.specification {
+            copy.specification = Some(self.property(specification)?);
+        }
+        for (originals, definitions) in [
+            (&original.definitions, &mut copy.definitions),
+            (&original.defs, &mut copy.defs),
+        ] {
+            if let (Some(originals), Some(definitions)) = (originals, definitions) {
+                for (name, definition) in originals {
+                    definitions.insert(name.clone(), self.definition(definition)?);
+                }
+            }
+        }
+        Ok(copy)
+    }
+}
+
+pub(crate) fn dereferenced(schema: &Schema, recursive: RecursiveRefs) -> Result<Schema> {
+    match &schema.0 {
+        SchemaInner::Schema(definition) => {
+            let mut inliner = Inliner {
+                schema,
+                recursive,
+                siblings: schema
+                    .draft()
+                    .is_none_or(|draft| draft >= Draft::Draft201909),
+                ancestors: Vec::new(),
+            };
+            Ok(Schema(SchemaInner::Schema(inliner.definition(definition)?)))
+        }
+        SchemaInner::Boolean(_) => Ok(schema.clone()),
+    }
+}
@@ -0,0 +1,784 @@
+//! Comparing two versions of a schema
+//!
+//! ```
+//! use serde_json::json;
+//! use serde_json_schema::diff::{schema_diff, ChangeKind, Impact};
+//! use serde_json_schema::{Schema, TryFrom};
+//!
+//! let old = Schema::try_from(json!({"type": "object", "properties": {"id": {"type": "number"}}})).unwrap();
+//! let new = Schema::try_from(json!({
+//!     "type": "object",
+//!     "properties": {"id": {"type": "integer"}},
+//!     "required": ["id"]
+//! }))
+//! .unwrap();
+//!
+//! let changes = schema_diff(&old, &new);
+//! assert_eq!(changes[0].pointer, "/properties/id/type");
+//! assert_eq!(changes[1].kind, ChangeKind::BecameRequired("id".into()));
+//! assert_eq!(Impact::of(&changes), Impact::ForwardCompatible);
+//! ```
+
+use serde_json::{Map, Value};
+
+use std::collections::BTreeSet;
+
+use crate::applicator::Applicators;
+use crate::property::{Constraints, ItemsOf, ObjectKeywords, Property};
+use crate::subset::{LOWER_BOUNDS, UPPER_BOUNDS};
+use crate::validation::equal;
+use crate::{Schema, SchemaDefinition, SchemaMap};
+
+/// Whose instances are still accepted after a change.
+///
+/// Backward compatible changes let the new schema accept everything the old one did, so readers
+/// can upgrade first. Forward compatible changes let the old schema accept everything the new one
+/// does, so writers can upgrade first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Impact {
+    /// Both schemas accept the same instances, e.g. only a `description` changed
+    FullyCompatible,
+    /// The new schema accepts more
+    BackwardCompatible,
+    /// The new schema accepts less
+    ForwardCompatible,
+    /// Either may reject what the other accepts, or this crate can't tell
+    Breaking,
+}
+
+impl Impact {
+    /// The impact of several changes together
+    pub fn of(changes: &[Change]) -> Impact {
+        changes
+            .iter()
+            .map(|change| change.impact)
+            .fold(Impact::FullyCompatible, Impact::and)
+    }
+
+    fn and(self, other: Impact) -> Impact {
+        match (self, other) {
+            (Impact::FullyCompatible, other) | (other, Impact::FullyCompatible) => other,
+            (a, b) if a == b => a,
+            _ => Impact::Breaking,
+        }
+    }
+
+    /// The impact of a change beneath `not`
+    fn negated(self) -> Impact {
+        match self {
+            Impact::BackwardCompatible => Impact::ForwardCompatible,
+            Impact::ForwardCompatible => Impact::BackwardCompatible,
+            other => other,
+        }
+    }
+}
+
+/// What changed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    PropertyAdded(String),
+    PropertyRemoved(String),
+    PatternPropertyAdded(String),
+    PatternPropertyRemoved(String),
+    BecameRequired(String),
+    NoLongerRequired(String),
+    /// The new `type` accepts all of the old ones and more, e.g. `integer` became `number`.
+    ///
+    /// A subschema without `type` is listed as `["any"]`.
+    TypeWidened {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    /// The old `type` accepts all of the new ones and more
+    TypeNarrowed {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    /// Neither `type` includes the other
+    TypeChanged {
+        old: Vec<String>,
+        new: Vec<String>,
+    },
+    EnumValueAdded(Value),
+    EnumValueRemoved(Value),
+    /// A validation keyword such as `minimum` or `enum` now accepts less, or was added
+    ConstraintTightened {
+        keyword: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    /// A validation keyword now accepts more, or was removed
+    ConstraintLoosened {
+        keyword: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    /// A validation keyword like `pattern` changed in a way that can't be ordered
+    ConstraintChanged {
+        keyword: String,
+        old: Option<Value>,
+        new: Option<Value>,
+    },
+    ReferenceChanged {
+        old: String,
+        new: String,
+    },
+    /// A subschema was replaced by `true` or `false` or the other way around
+    SubschemaReplaced {
+        old: Value,
+        new: Value,
+    },
+    /// Subschemas of `oneOf`, `if` or the like changed, which this crate does not look into
+    SubschemasChanged(String),
+    /// `title`, `description` or another annotation changed
+    AnnotationChanged(String),
+    DefinitionAdded(String),
+    DefinitionRemoved(String),
+}
+
+/// A difference between two schemas
+#[derive(Debug, Clone, PartialEq)]
+pub struct Change {
+    /// JSON Pointer to the keyword that changed, within the new schema or, if it was removed,
+    /// within the old one
+    pub pointer: String,
+    pub kind: ChangeKind,
+    pub impact: Impact,
+}
+
+/// Validation keywords whose values can't be ordered
+const OTHER_CONSTRAINTS: &[&str] = &["pattern", "format", "const", "multipleOf", "uniqueItems"];
+
+/// Lists the changes from `old` to `new`, with their impact on existing instances.
+///
+/// This looks at the structure of both schemas, it does not follow `$ref`s but compares the
+/// definitions they point at by name.
+pub fn schema_diff(old: &Schema, new: &Schema) -> Vec<Change> {
+    let mut diff = Diff::default();
+    match (old.as_definition(), new.as_definition()) {
+        (Some(old), Some(new)) => diff.definition(old, new, ""),
+        _ => diff.subschema(
+            old.as_property().unwrap_or(&Property::Boolean(true)),
+            new.as_property().unwrap_or(&Property::Boolean(true)),
+            "",
+        ),
+    }
+    diff.changes
+}
+
+fn pointer(parent: &str, keyword: &str) -> String {
+    format!(
+        "{}/{}",
+        parent,
+        keyword.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+/// The types a subschema accepts in any order, `None` if it accepts all of them
+fn types<'a>(constraints: &Constraints<'a>) -> Option<BTreeSet<&'a str>> {
+    constraints
+        .types
+        .as_ref()
+        .map(|types| types.iter().copied().collect())
+}
+
+/// Whether the keywords of type `name` apply to some instances of both subschemas
+fn both_allow(old: &Option<BTreeSet<&str>>, new: &Option<BTreeSet<&str>>, name: &str) -> bool {
+    [old, new]
+        .iter()
+        .all(|types| types.as_ref().is_none_or(|types| types.contains(name)))
+}
+
+/// Whether every type of `narrow` is accepted by `wide`
+fn includes(wide: &Option<BTreeSet<&str>>, narrow: &Option<BTreeSet<&str>>) -> bool {
+    match (wide, narrow) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(wide), Some(narrow)) => narrow
+            .iter()
+            .all(|t| wide.contains(t) || (*t == "integer" && wide.contains("number"))),
+    }
+}
+
+fn type_names(types: &Option<BTreeSet<&str>>) -> Vec<String> {
+    match types {
+        Some(types) => types.iter().map(|t| t.to_string()).collect(),
+        None => vec!["any".into()],
+    }
+}
+
+/// The subschemas of `items` by position, then the one for the elements after them and the
+/// keyword it is found under
+fn items<'a>(constraints: &Constraints<'a>) -> (&'a [Property], Property, &'static str) {
+    match constraints.items {
+        Some(ItemsOf::Positional(items)) => {
+            let rest = constraints
+                .extra
+                .get("additionalItems")
+                .and_then(|rest| serde_json::from_value(rest.clone()).ok());
+            (
+                items,
+                rest.unwrap_or(Property::Boolean(true)),
+                "additionalItems",
+            )
+        }
+        Some(ItemsOf::Each(items)) => (&[], items.clone(), "items"),
+        None => (&[], Property::Boolean(true), "items"),
+    }
+}
+
+/// The keywords of a subschema as they appear in the document
+fn keywords(property: &Property) -> Map<String, Value> {
+    match serde_json::to_value(property) {
+        Ok(Value::Object(keywords)) => keywords,
+        _ => Map::new(),
+    }
+}
+
+#[derive(Default)]
+struct Diff {
+    changes: Vec<Change>,
+    /// Whether the current subschema is beneath an odd number of `not`s
+    negated: bool,
+}
+
+impl Diff {
+    fn push(&mut self, pointer: String, kind: ChangeKind, impact: Impact) {
+        let impact = match self.negated {
+            true => impact.negated(),
+            false => impact,
+        };
+        self.changes.push(Change {
+            pointer,
+            kind,
+            impact,
+        });
+    }
+
+    fn definition(&mut self, old: &SchemaDefinition, new: &SchemaDefinition, at: &str) {
+        match (&old.specification, &new.specification) {
+            (Some(old), Some(new)) => self.subschema(old, new, at),
+            (None, Some(new)) => self.subschema(&Property::Boolean(true), new, at),
+            (Some(old), None) => self.subschema(old, &Property::Boolean(true), at),
+            (None, None) => {}
+        }
+        let dependencies = |definition: &SchemaDefinition| {
+            let mut keywords = Map::new();
+            if let Some(dependencies) = &definition.dependencies {
+                let dependencies = serde_json::to_value(dependencies).unwrap_or(Value::Null);
+                keywords.insert("dependencies".into(), dependencies);
+            }
+            keywords
+        };
+        self.dependencies(&dependencies(old), &dependencies(new), at);
+        for (keyword, old, new) in [
+            ("definitions", &old.definitions, &new.definitions),
+            ("$defs", &old.defs, &new.defs),
+        ] {
+            let empty = SchemaMap::default();
+            let (old, new) = (
+                old.as_ref().unwrap_or(&empty),
+                new.as_ref().unwrap_or(&empty),
+            );
+            let at = pointer(at, keyword);
+            for (name, old) in old {
+                match new.get(name) {
+                    Some(new) => self.definition(old, new, &pointer(&at, name)),
+                    None => self.push(
+                        pointer(&at, name),
+                        ChangeKind::DefinitionRemoved(name.clone()),
+                        Impact::Breaking,
+                    ),
+                }
+            }
+            for name in new.keys().filter(|name| !old.contains_key(*name)) {
+                self.push(
+                    pointer(&at, name),
+                    ChangeKind::DefinitionAdded(name.clone()),
+                    Impact::FullyCompatible,
+                );
+            }
+        }
+    }
+
+    fn subschema(&mut self, old: &Property, new: &Property, at: &str) {
+        if old == new {
+            return;
+        }
+        match (old, new) {
+            (Property::Boolean(_), _) | (_, Property::Boolean(_)) => {
+                let impact = match (old, new) {
+                    (Property::Boolean(true), _) | (_, Property::Boolean(false)) => {
+                        Impact::ForwardCompatible
+                    }
+                    _ => Impact::BackwardCompatible,
+                };
+                let kind = ChangeKind::SubschemaReplaced {
+                    old: serde_json::to_value(old).unwrap_or(Value::Null),
+                    new: serde_json::to_value(new).unwrap_or(Value::Null),
+                };
+                return self.push(at.to_owned(), kind, impact);
+            }
+            (Property::Ref(old), Property::Ref(new)) if old.reference == new.reference => {}
+            (Property::Ref(_), _) | (_, Property::Ref(_)) => {
+                let reference = |property: &Property| match property {
+                    Property::Ref(reference) => reference.reference.clone(),
+                    _ => String::new(),
+                };
+                self.push(
+                    pointer(at, "$ref"),
+                    ChangeKind::ReferenceChanged {
+                        old: reference(old),
+                        new: reference(new),
+                    },
+                    Impact::Breaking,
+                );
+            }
+            _ => self.types(&old.constraints(), &new.constraints(), at),
+        }
+
+        self.annotations(old, new, at);
+        let (old_keywords, new_keywords) = (keywords(old), keywords(new));
+        self.constraints(&old_keywords, &new_keywords, at);
+        self.enumeration(&old_keywords, &new_keywords, at);
+        self.dependencies(&old_keywords, &new_keywords, at);
+
+        // the keywords of a type only matter if both subschemas accept some of its instances
+        let (old, new) = (old.constraints(), new.constraints());
+        let (old_types, new_types) = (types(&old), types(&new));
+        if both_allow(&old_types, &new_types, "object") {
+            self.object(&old.object, &new.object, at);
+        }
+        if both_allow(&old_types, &new_types, "array") {
+            self.items(&old, &new, at);
+        }
+        self.applicators(old.applicators, new.applicators, at);
+    }
+
+    fn types(&mut self, old: &Constraints, new: &Constraints, at: &str) {
+        let (old, new) = (types(old), types(new));
+        if old == new {
+            return;
+        }
+        let (names_old, names_new) = (type_names(&old), type_names(&new));
+        let (kind, impact) = match (includes(&new, &old), includes(&old, &new)) {
+            (true, _) => (
+                ChangeKind::TypeWidened {
+                    old: names_old,
+                    new: names_new,
+                },
+                Impact::BackwardCompatible,
+            ),
+            (false, true) => (
+                ChangeKind::TypeNarrowed {
+                    old: names_old,
+                    new: names_new,
+                },
+                Impact::ForwardCompatible,
+            ),
+            (false, false) => (
+                ChangeKind::TypeChanged {
+                    old: names_old,
+                    new: names_new,
+                },
+                Impact::Breaking,
+            ),
+        };
+        self.push(pointer(at, "type"), kind, impact);
+    }
+
+    fn annotations(&mut self, old: &Property, new: &Property, at: &str) {
+        let (old, new) = (
+            serde_json::to_value(old.annotations()).unwrap_or(Value::Null),
+            serde_json::to_value(new.annotations()).unwrap_or(Value::Null),
+        );
+        let empty = Map::new();
+        let (old, new) = (
+            old.as_object().unwrap_or(&empty),
+            new.as_object().unwrap_or(&empty),
+        );
+        let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+        for name in names {
+            if old.get(name) != new.get(name) {
+                self.push(
+                    pointer(at, name),
+                    ChangeKind::AnnotationChanged(name.clone()),
+                    Impact::FullyCompatible,
+                );
+            }
+        }
+    }
+
+    fn constraints(&mut self, old: &Map<String, Value>, new: &Map<String, Value>, at: &str) {
+        let bounds = LOWER_BOUNDS
+            .iter()
+            .map(|keyword| (*keyword, Some(true)))
+            .chain(UPPER_BOUNDS.iter().map(|keyword| (*keyword, Some(false))))
+            .chain(OTHER_CONSTRAINTS.iter().map(|keyword| (*keyword, None)));
+        for (keyword, lower) in bounds {
+            let (old, new) = (old.get(keyword), new.get(keyword));
+            if old == new {
+                continue;
+            }
+            let tighter = match (old, new, lower) {
+                (None, Some(_), _) => Some(true),
+                (Some(_), None, _) => Some(false),
+                (Some(Value::Bool(old)), Some(Value::Bool(new)), None)
+                    if keyword == "uniqueItems" =>
+                {
+                    Some(*new && !old)
+                }
+                (Some(old), Some(new), Some(lower)) => match (old.as_f64(), new.as_f64()) {
+                    (Some(old), Some(new)) => Some((new > old) == lower),
+                    _ => None,
+                },
+                _ => None,
+            };
+            self.constraint(pointer(at, keyword), keyword, (old, new), tighter);
+        }
+    }
+
+    /// Reports that `keyword` changed, `tighter` tells whether it accepts less, more or either
+    fn constraint(
+        &mut self,
+        at: String,
+        keyword: &str,
+        (old, new): (Option<&Value>, Option<&Value>),
+        tighter: Option<bool>,
+    ) {
+        let (old, new) = (old.cloned(), new.cloned());
+        let keyword = keyword.to_owned();
+        let (kind, impact) = match tighter {
+            Some(true) => (
+                ChangeKind::ConstraintTightened { keyword, old, new },
+                Impact::ForwardCompatible,
+            ),
+            Some(false) => (
+                ChangeKind::ConstraintLoosened { keyword, old, new },
+                Impact::BackwardCompatible,
+            ),
+            None => (
+                ChangeKind::ConstraintChanged { keyword, old, new },
+                Impact::Breaking,
+            ),
+        };
+        self.push(at, kind, impact);
+    }
+
+    /// Properties that require others, or that the object matches more subschemas
+    fn dependencies(&mut self, old: &Map<String, Value>, new: &Map<String, Value>, at: &str) {
+        for keyword in ["dependencies", "dependentRequired", "dependentSchemas"] {
+            let empty = Map::new();
+            let (old, new) = (
+                old.get(keyword)
+                    .and_then(Value::as_object)
+                    .unwrap_or(&empty),
+                new.get(keyword)
+                    .and_then(Value::as_object)
+                    .unwrap_or(&empty),
+            );
+            let names: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for name in names {
+                let (old, new) = (old.get(name), new.get(name));
+                let tighter = match (old, new) {
+                    (None, Some(_)) => Some(true),
+                    (Some(_), None) => Some(false),
+                    (Some(Value::Array(old)), Some(Value::Array(new))) => {
+                        match (
+                            old.iter().all(|name| new.contains(name)),
+                            new.iter().all(|name| old.contains(name)),
+                        ) {
+                            (true, true) => continue,
+                            (true, false) => Some(true),
+                            (false, true) => Some(false),
+                            (false, false) => None,
+                        }
+                    }
+                    (old, new) if old == new => continue,
+                    _ => None,
+                };
+                let at = pointer(&pointer(at, keyword), name);
+                self.constraint(at, keyword, (old, new), tighter);
+            }
+        }
+    }
+
+    fn enumeration(&mut self, old: &Map<String, Value>, new: &Map<String, Value>, at: &str) {
+        let at = pointer(at, "enum");
+        match (old.get("enum"), new.get("enum")) {
+            (Some(Value::Array(old)), Some(Value::Array(new))) => {
+                let contains = |values: &[Value], value: &Value| {
+                    values.iter().any(|other| equal(value, other))
+                };
+                for value in old.iter().filter(|value| !contains(new, value)) {
+                    self.push(
+                        at.clone(),
+                        ChangeKind::EnumValueRemoved(value.clone()),
+                        Impact::ForwardCompatible,
+                    );
+                }
+                for value in new.iter().filter(|value| !contains(old, value)) {
+                    self.push(
+                        at.clone(),
+                        ChangeKind::EnumValueAdded(value.clone()),
+                        Impact::BackwardCompatible,
+                    );
+                }
+            }
+            (None, None) => {}
+            (old, new) if old == new => {}
+            (None, new) => self.push(
+                at,
+                ChangeKind::ConstraintTightened {
+                    keyword: "enum".into(),
+                    old: None,
+                    new: new.cloned(),
+                },
+                Impact::ForwardCompatible,
+            ),
+            (old, None) => self.push(
+                at,
+                ChangeKind::ConstraintLoosened {
+                    keyword: "enum".into(),
+                    old: old.cloned(),
+                    new: None,
+                },
+                Impact::BackwardCompatible,
+            ),
+            (old, new) => self.push(
+                at,
+                ChangeKind::ConstraintChanged {
+                    keyword: "enum".into(),
+                    old: old.cloned(),
+                    new: new.cloned(),
+                },
+                Impact::Breaking,
+            ),
+        }
+    }
+
+    fn object(&mut self, old: &ObjectKeywords, new: &ObjectKeywords, at: &str) {
+        let empty = SchemaMap::default();
+        let (old_properties, new_properties) = (
+            old.properties.unwrap_or(&empty),
+            new.properties.unwrap_or(&empty),
+        );
+        let (old_additional, new_additional) =
+            (old.additional_properties, new.additional_properties);
+        let properties = pointer(at, "properties");
+        for (name, old) in old_properties {
+            let at = pointer(&properties, name);
+            match new_properties.get(name) {
+                Some(new) => self.subschema(old, new, &at),
+                None => {
+                    // what the new schema accepts under that name now
+                    let impact = match (old, new_additional) {
+                        (Property::Boolean(true), None | Some(Property::Boolean(true))) => {
+                            Impact::FullyCompatible
+                        }
+                        (_, None | Some(Property::Boolean(true))) => Impact::BackwardCompatible,
+                        (_, Some(Property::Boolean(false))) => Impact::ForwardCompatible,
+                        (_, Some(_)) => Impact::Breaking,
+                    };
+                    self.push(at, ChangeKind::PropertyRemoved(name.clone()), impact);
+                }
+            }
+        }
+        for (name, new) in new_properties {
+            if old_properties.contains_key(name) {
+                continue;
+            }
+            // what the old schema accepted under that name
+            let impact = match (old_additional, new) {
+                (None | Some(Property::Boolean(true)), Property::Boolean(true)) => {
+                    Impact::FullyCompatible
+                }
+                (_, Property::Boolean(true)) => Impact::BackwardCompatible,
+                (None, _) | (Some(Property::Boolean(true)), _) => Impact::ForwardCompatible,
+                (Some(Property::Boolean(false)), _) => Impact::BackwardCompatible,
+                (Some(_), _) => Impact::Breaking,
+            };
+            self.push(
+                pointer(&properties, name),
+                ChangeKind::PropertyAdded(name.clone()),
+                impact,
+            );
+        }
+
+        // a pattern also applies to the `properties` it matches
+        let (old_patterns, new_patterns) = (
+            old.pattern_properties.unwrap_or(&empty),
+            new.pattern_properties.unwrap_or(&empty),
+        );
+        let patterns = pointer(at, "patternProperties");
+        for (pattern, old) in old_patterns {
+            let at = pointer(&patterns, pattern);
+            match new_patterns.get(pattern) {
+                Some(new) => self.subschema(old, new, &at),
+                None => {
+                    let impact = match (old, new_additional) {
+                        (Property::Boolean(true), None | Some(Property::Boolean(true))) => {
+                            Impact::FullyCompatible
+                        }
+                        (_, None | Some(Property::Boolean(true))) => Impact::BackwardCompatible,
+                        (Property::Boolean(true), Some(Property::Boolean(false))) => {
+                            Impact::ForwardCompatible
+                        }
+                        (_, Some(_)) => Impact::Breaking,
+                    };
+                    self.push(
+                        at,
+                        ChangeKind::PatternPropertyRemoved(pattern.clone()),
+                        impact,
+                    );
+                }
+            }
+        }
+        for (pattern, new) in new_patterns {
+            if old_patterns.contains_key(pattern) {
+                continue;
+            }
+            let impact = match (old_additional, new) {
+                (None | Some(Property::Boolean(true)), Property::Boolean(true)) => {
+                    Impact::FullyCompatible
+                }
+                (None | Some(Property::Boolean(true)), _) => Impact::ForwardCompatible,
+                (Some(Property::Boolean(false)), Property::Boolean(true)) => {
+                    Impact::BackwardCompatible
+                }
+                (Some(_), _) => Impact::Breaking,
+            };
+            self.push(
+                pointer(&patterns, pattern),
+                ChangeKind::PatternPropertyAdded(pattern.clone()),
+                impact,
+            );
+        }
+
+        for name in new
+            .required
+            .iter()
+            .filter(|name| !old.required.contains(name))
+        {
+            self.push(
+                pointer(at, "required"),
+                ChangeKind::BecameRequired(name.clone()),
+                Impact::ForwardCompatible,
+            );
+        }
+        for name in old
+            .required
+            .iter()
+            .filter(|name| !new.required.contains(name))
+        {
+            self.push(
+                pointer(at, "required"),
+                ChangeKind::NoLongerRequired(name.clone()),
+                Impact::BackwardCompatible,
+            );
+        }
+
+        let anything = Property::Boolean(true);
+        self.subschema(
+            old_additional.unwrap_or(&anything),
+            new_additional.unwrap_or(&anything),
+            &pointer(at, "additionalProperties"),
+        );
+    }
+
+    /// Compares the elements position by position, `items` that apply to every element are
+    /// like an empty list of positions followed by `additionalItems`
+    fn items(&mut self, old: &Constraints, new: &Constraints, at: &str) {
+        let (old_positions, old_rest, _) = items(old);
+        let (new_positions, new_rest, rest_keyword) = items(new);
+        let positions = pointer(at, "items");
+        for index in 0..old_positions.len().max(new_positions.len()) {
+            self.subschema(
+                old_positions.get(index).unwrap_or(&old_rest),
+                new_positions.get(index).unwrap_or(&new_rest),
+                &pointer(&positions, &index.to_string()),
+            );
+        }
+        self.subschema(&old_rest, &new_rest, &pointer(at, rest_keyword));
+    }
+
+    fn applicators(&mut self, old: &Applicators, new: &Applicators, at: &str) {
+        // keywords that accept less when one of their subschemas does
+        for (keyword, old, new) in [
+            ("then", &old.then, &new.then),
+            ("else", &old.r#else, &new.r#else),
+            (
+                "unevaluatedProperties",
+                &old.unevaluated_properties,
+                &new.unevaluated_properties,
+            ),
+            (
+                "unevaluatedItems",
+                &old.unevaluated_items,
+                &new.unevaluated_items,
+            ),
+        ] {
+            let anything = Property::Boolean(true);
+            self.subschema(
+                old.as_deref().unwrap_or(&anything),
+                new.as_deref().unwrap_or(&anything),
+                &pointer(at, keyword),
+            );
+        }
+
+        if old.not != new.not {
+            let nothing = Property::Boolean(false);
+            self.negated = !self.negated;
+            self.subschema(
+                old.not.as_deref().unwrap_or(&nothing),
+                new.not.as_deref().unwrap_or(&nothing),
+                &pointer(at, "not"),
+            );
+            self.negated = !self.negated;
+        }
+
+        // an additional subschema of `allOf` accepts less, one of `anyOf` more
+        for (keyword, old, new, added) in [
+            ("allOf", &old.all_of, &new.all_of, Impact::ForwardCompatible),
+            (
+                "anyOf",
+                &old.any_of,
+                &new.any_of,
+                Impact::BackwardCompatible,
+            ),
+        ] {
+            let (old, new) = (
+                old.as_deref().unwrap_or_default(),
+                new.as_deref().unwrap_or_default(),
+            );
+            let at = pointer(at, keyword);
+            if old.len() == new.len() {
+                for (index, (old, new)) in old.iter().zip(new).enumerate() {
+                    self.subschema(old, new, &pointer(&at, &index.to_string()));
+                }
+                continue;
+            }
+            let added_any = new.iter().any(|subschema| !old.contains(subschema));
+            let removed_any = old.iter().any(|subschema| !new.contains(subschema));
+            let impact = match (added_any, removed_any) {
+                (true, false) => added,
+                (false, true) => added.negated(),
+                _ => Impact::Breaking,
+            };
+            self.push(at, ChangeKind::SubschemasChanged(keyword.into()), impact);
+        }
+
+        for (keyword, changed) in [
+            ("oneOf", old.one_of != new.one_of),
+            ("if", old.r#if != new.r#if),
+        ] {
+            if changed {
+                self.push(
+                    pointer(at, keyword),
+                    ChangeKind::SubschemasChanged(keyword.into()),
+                    Impact::Breaking,
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Versions of the json-schema specification
+
+use url::Url;
+
+/// A published version of the json-schema specification
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Draft {
+    Draft04,
+    Draft06,
+    Draft07,
+    Draft201909,
+    Draft202012,
+}
+
+impl Draft {
+    /// Recognizes the meta-schema urls used as `$schema`
+    pub fn from_url(url: &Url) -> Option<Draft> {
+        if url.host_str() != Some("json-schema.org") {
+            return None;
+        }
+        match url.path() {
+            "/draft-04/schema" => Some(Draft::Draft04),
+            "/draft-06/schema" => Some(Draft::Draft06),
+            "/draft-07/schema" => Some(Draft::Draft07),
+            "/draft/2019-09/schema" => Some(Draft::Draft201909),
+            "/draft/2020-12/schema" => Some(Draft::Draft202012),
+            _ => None,
+        }
+    }
+
+    /// The url of the meta-schema of this draft, as used for `$schema`
+    pub fn url(&self) -> Url {
+        let url = match self {
+            Draft::Draft04 => "http://json-schema.org/draft-04/schema#",
+            Draft::Draft06 => "http://json-schema.org/draft-06/schema#",
+            Draft::Draft07 => "http://json-schema.org/draft-07/schema#",
+            Draft::Draft201909 => "https://json-schema.org/draft/2019-09/schema",
+            Draft::Draft202012 => "https://json-schema.org/draft/2020-12/schema",
+        };
+        Url::parse(url).expect("meta-schema urls are valid")
+    }
+}
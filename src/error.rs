@@ -17,12 +17,49 @@ pub struct Error {
     kind: ErrorKind,
 }
 
+impl Error {
+    /// The kind of this error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// An error of a user defined [`Keyword`](crate::keyword::Keyword)
+    pub fn keyword(message: impl Into<String>) -> Error {
+        ErrorKind::Keyword(message.into()).into()
+    }
+}
+
 impl std::error::Error for Error {}
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self.kind {
             ErrorKind::SerdeJson(ref e) => write!(f, "{}", e),
+            ErrorKind::UnresolvableRef(ref reference) => {
+                write!(f, "unable to resolve reference {:?}", reference)
+            }
+            ErrorKind::RefCycle(ref chain) => {
+                write!(f, "reference cycle detected: {}", chain.join(" -> "))
+            }
+            ErrorKind::RecursiveRef(ref reference) => {
+                write!(
+                    f,
+                    "reference {:?} is recursive and can't be inlined",
+                    reference
+                )
+            }
+            ErrorKind::Keyword(ref message) => f.write_str(message),
+            ErrorKind::InvalidPointer(ref pointer) => {
+                write!(f, "{:?} does not locate a subschema", pointer)
+            }
+            ErrorKind::Conflict {
+                ref pointer,
+                ref keyword,
+            } => write!(
+                f,
+                "the allOf at {:?} can't be satisfied, {:?} conflicts",
+                pointer, keyword
+            ),
         }
     }
 }
@@ -31,6 +68,24 @@ impl fmt::Display for Error {
 #[derive(Debug)]
 pub enum ErrorKind {
     SerdeJson(serde_json::Error),
+    /// A `$ref` that does not point at a subschema within the document.
+    UnresolvableRef(String),
+    /// A chain of `$ref`s that leads back to itself without ever reaching a subschema.
+    ///
+    /// Contains every visited reference, starting and ending with the repeated one.
+    RefCycle(Vec<String>),
+    /// A `$ref` that points back at one of its own ancestors.
+    RecursiveRef(String),
+    /// Raised by a user defined [`Keyword`](crate::keyword::Keyword).
+    Keyword(String),
+    /// A JSON Pointer that does not locate a subschema to insert or remove.
+    InvalidPointer(String),
+    /// The subschemas of an `allOf` at `pointer` that no instance satisfies together, e.g. because
+    /// their `type`s don't overlap.
+    Conflict {
+        pointer: String,
+        keyword: String,
+    },
 }
 
 impl From<serde_json::Error> for Error {
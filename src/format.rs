@@ -0,0 +1,258 @@
+//! Checkers for the [formats](https://json-schema.org/draft/2020-12/json-schema-validation.html#section-7.3)
+//! defined by the specification.
+//!
+//! `format` is only an annotation by default, these are applied when
+//! [`ValidationOptions::assert_formats`](crate::ValidationOptions::assert_formats) is enabled.
+
+use json_pointer::JsonPointer;
+use url::Url;
+
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::Arc;
+
+/// A custom format, registered with [`ValidationOptions::format`](crate::ValidationOptions::format)
+pub type FormatChecker = Arc<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// The formats that this crate knows how to check
+pub const KNOWN_FORMATS: &[&str] = &[
+    "date-time",
+    "date",
+    "time",
+    "duration",
+    "email",
+    "hostname",
+    "ipv4",
+    "ipv6",
+    "uri",
+    "uri-reference",
+    "uuid",
+    "json-pointer",
+    "regex",
+];
+
+/// Checks `value` against one of the [`KNOWN_FORMATS`].
+///
+/// Returns `None` if `format` is not one of them.
+pub fn check(format: &str, value: &str) -> Option<bool> {
+    let valid = match format {
+        "date-time" => is_date_time(value),
+        "date" => is_date(value),
+        "time" => is_time(value),
+        "duration" => is_duration(value),
+        "email" => is_email(value),
+        "hostname" => is_hostname(value),
+        "ipv4" => value.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => value.parse::<Ipv6Addr>().is_ok(),
+        "uri" => is_uri(value),
+        "uri-reference" => is_uri_reference(value),
+        "uuid" => is_uuid(value),
+        "json-pointer" => is_json_pointer(value),
+        "regex" => regex::Regex::new(value).is_ok(),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+/// Parses exactly `len` ascii digits
+fn digits(s: &str, len: usize) -> Option<u32> {
+    if s.len() == len && s.bytes().all(|b| b.is_ascii_digit()) {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// `full-date` from [RFC 3339](https://tools.ietf.org/html/rfc3339#section-5.6)
+fn is_date(value: &str) -> bool {
+    let mut parts = value.split('-');
+    let (Some(year), Some(month), Some(day), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let (Some(year), Some(month), Some(day)) = (digits(year, 4), digits(month, 2), digits(day, 2))
+    else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+    (1..=days_in_month).contains(&day)
+}
+
+/// `full-time` from [RFC 3339](https://tools.ietf.org/html/rfc3339#section-5.6), the offset is required
+fn is_time(value: &str) -> bool {
+    let value = value.to_ascii_uppercase();
+    let (time, offset) = if let Some(time) = value.strip_suffix('Z') {
+        (time, None)
+    } else {
+        match value.rfind(['+', '-']) {
+            Some(split) => (&value[..split], Some(&value[split + 1..])),
+            None => return false,
+        }
+    };
+
+    let (time, fraction) = match time.split_once('.') {
+        Some((time, fraction)) => (time, Some(fraction)),
+        None => (time, None),
+    };
+    if fraction.is_some_and(|f| f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit())) {
+        return false;
+    }
+
+    let mut parts = time.split(':');
+    let (Some(hour), Some(minute), Some(second), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return false;
+    };
+    let (Some(hour), Some(minute), Some(second)) =
+        (digits(hour, 2), digits(minute, 2), digits(second, 2))
+    else {
+        return false;
+    };
+    // 60 is a leap second
+    if hour > 23 || minute > 59 || second > 60 {
+        return false;
+    }
+
+    match offset {
+        None => true,
+        Some(offset) => match offset.split_once(':') {
+            Some((hours, minutes)) => matches!(
+                (digits(hours, 2), digits(minutes, 2)),
+                (Some(hours), Some(minutes)) if hours < 24 && minutes < 60
+            ),
+            None => false,
+        },
+    }
+}
+
+/// `date-time` from [RFC 3339](https://tools.ietf.org/html/rfc3339#section-5.6)
+fn is_date_time(value: &str) -> bool {
+    match value.find(['T', 't']) {
+        Some(split) => is_date(&value[..split]) && is_time(&value[split + 1..]),
+        None => false,
+    }
+}
+
+/// `duration` from [RFC 3339, appendix A](https://tools.ietf.org/html/rfc3339#appendix-A)
+fn is_duration(value: &str) -> bool {
+    /// Consumes `<digits><designator>` pairs, designators have to appear in the given order
+    fn units(mut s: &str, designators: &[char]) -> Option<usize> {
+        let mut count = 0;
+        let mut allowed = designators;
+        while !s.is_empty() {
+            let number = s.find(|c: char| !c.is_ascii_digit())?;
+            if number == 0 {
+                return None;
+            }
+            let designator = s[number..].chars().next()?;
+            let position = allowed.iter().position(|d| *d == designator)?;
+            allowed = &allowed[position + 1..];
+            s = &s[number + 1..];
+            count += 1;
+        }
+        Some(count)
+    }
+
+    let Some(duration) = value.strip_prefix('P') else {
+        return false;
+    };
+    if let Some(weeks) = duration.strip_suffix('W') {
+        return !weeks.is_empty() && weeks.bytes().all(|b| b.is_ascii_digit());
+    }
+    let (date, time) = match duration.split_once('T') {
+        Some((date, time)) => (date, Some(time)),
+        None => (duration, None),
+    };
+    let Some(date_units) = units(date, &['Y', 'M', 'D']) else {
+        return false;
+    };
+    match time {
+        Some(time) => matches!(units(time, &['H', 'M', 'S']), Some(n) if n > 0),
+        None => date_units > 0,
+    }
+}
+
+/// A hostname as of [RFC 1123](https://tools.ietf.org/html/rfc1123#section-2.1)
+fn is_hostname(value: &str) -> bool {
+    let value = value.strip_suffix('.').unwrap_or(value);
+    !value.is_empty()
+        && value.len() <= 253
+        && value.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+        })
+}
+
+/// The `addr-spec` of [RFC 5321](https://tools.ietf.org/html/rfc5321#section-4.1.2),
+/// without quoted local parts
+fn is_email(value: &str) -> bool {
+    let Some((local, domain)) = value.rsplit_once('@') else {
+        return false;
+    };
+    let local_ok = !local.is_empty()
+        && local.len() <= 64
+        && local.split('.').all(|atom| {
+            !atom.is_empty()
+                && atom
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "!#$%&'*+-/=?^_`{|}~".contains(c))
+        });
+    let domain_ok = match domain.strip_prefix('[').and_then(|d| d.strip_suffix(']')) {
+        Some(literal) => match literal.strip_prefix("IPv6:") {
+            Some(v6) => v6.parse::<Ipv6Addr>().is_ok(),
+            None => literal.parse::<Ipv4Addr>().is_ok(),
+        },
+        None => is_hostname(domain),
+    };
+    local_ok && domain_ok
+}
+
+/// Characters that may never appear in a URI, not even in a relative one
+fn has_invalid_uri_characters(value: &str) -> bool {
+    value
+        .chars()
+        .any(|c| c.is_whitespace() || c.is_control() || "\\\"<>^`{|}".contains(c))
+}
+
+/// An absolute URI as of [RFC 3986](https://tools.ietf.org/html/rfc3986)
+fn is_uri(value: &str) -> bool {
+    !has_invalid_uri_characters(value) && value.is_ascii() && Url::parse(value).is_ok()
+}
+
+/// A URI or relative reference as of [RFC 3986](https://tools.ietf.org/html/rfc3986)
+fn is_uri_reference(value: &str) -> bool {
+    if has_invalid_uri_characters(value) || !value.is_ascii() {
+        return false;
+    }
+    let base = Url::parse("http://example.invalid/").expect("a valid base url");
+    base.join(value).is_ok()
+}
+
+/// `8-4-4-4-12` hexadecimal digits, as of [RFC 4122](https://tools.ietf.org/html/rfc4122#section-3)
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.len() == 5
+        && groups
+            .iter()
+            .zip([8, 4, 4, 4, 12])
+            .all(|(group, len)| group.len() == len && group.bytes().all(|b| b.is_ascii_hexdigit()))
+}
+
+/// A JSON Pointer in its string representation, as of [RFC 6901](https://tools.ietf.org/html/rfc6901)
+fn is_json_pointer(value: &str) -> bool {
+    // the parser also accepts the URI fragment representation
+    !value.starts_with('#') && value.parse::<JsonPointer<String, Vec<String>>>().is_ok()
+}
@@ -0,0 +1,255 @@
+//! Deriving a schema from sample documents
+
+use serde_json::Value;
+
+use std::collections::BTreeSet;
+
+use crate::builder::SchemaBuilder;
+use crate::format;
+use crate::property::{AnyProperty, Property, Types, UntypedKeywords};
+use crate::{Schema, SchemaMap};
+
+/// The formats [`InferenceOptions::formats`] looks for, the first one all samples match is taken
+const INFERRED_FORMATS: &[&str] = &["date-time", "date", "uuid", "email", "ipv4", "ipv6", "uri"];
+
+/// Configures [`Schema::infer_with`]
+///
+/// ```
+/// use serde_json_schema::InferenceOptions;
+///
+/// let options = InferenceOptions::new().formats(true).enums(5);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct InferenceOptions {
+    formats: bool,
+    enums: Option<usize>,
+}
+
+impl InferenceOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the `format` of strings if every sample is e.g. a `date-time`, `uuid` or `email`
+    pub fn formats(mut self, formats: bool) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Lists the values of strings as `enum` if there are at most `max` different ones.
+    ///
+    /// Only if values repeat, a string that differs in every sample is not an enumeration.
+    pub fn enums(mut self, max: usize) -> Self {
+        self.enums = Some(max);
+        self
+    }
+}
+
+/// Everything the samples at one location have shown so far
+#[derive(Debug, Default)]
+struct Shape {
+    null: bool,
+    boolean: bool,
+    integer: bool,
+    number: bool,
+    strings: Option<Strings>,
+    objects: Option<Objects>,
+    items: Option<Box<Shape>>,
+}
+
+#[derive(Debug)]
+struct Strings {
+    count: usize,
+    /// Distinct values, only kept while they could still form an `enum`
+    values: Option<BTreeSet<String>>,
+    formats: Vec<&'static str>,
+}
+
+#[derive(Debug, Default)]
+struct Objects {
+    count: usize,
+    /// Each property with the number of objects that have it
+    properties: SchemaMap<(usize, Shape)>,
+}
+
+impl Shape {
+    fn add(&mut self, sample: &Value, options: &InferenceOptions) {
+        match sample {
+            Value::Null => self.null = true,
+            Value::Bool(_) => self.boolean = true,
+            Value::Number(number) if number.is_i64() || number.is_u64() => self.integer = true,
+            Value::Number(_) => self.number = true,
+            Value::String(value) => {
+                let strings = self.strings.get_or_insert_with(|| Strings {
+                    count: 0,
+                    values: options.enums.map(|_| BTreeSet::new()),
+                    formats: match options.formats {
+                        true => INFERRED_FORMATS.to_vec(),
+                        false => Vec::new(),
+                    },
+                });
+                strings.count += 1;
+                strings
+                    .formats
+                    .retain(|name| format::check(name, value) == Some(true));
+                if let (Some(values), Some(max)) = (&mut strings.values, options.enums) {
+                    values.insert(value.clone());
+                    if values.len() > max {
+                        strings.values = None;
+                    }
+                }
+            }
+            Value::Array(elements) => {
+                let items = self.items.get_or_insert_with(Box::default);
+                for element in elements {
+                    items.add(element, options);
+                }
+            }
+            Value::Object(object) => {
+                let objects = self.objects.get_or_insert_with(Objects::default);
+                objects.count += 1;
+                for (name, value) in object {
+                    let (count, shape) = objects.properties.entry(name.clone()).or_default();
+                    *count += 1;
+                    shape.add(value, options);
+                }
+            }
+        }
+    }
+
+    /// The `type` of each kind of sample that was seen
+    fn names(&self) -> Vec<&'static str> {
+        let mut names = Vec::new();
+        if self.boolean {
+            names.push("boolean");
+        }
+        match (self.integer, self.number) {
+            (true, false) => names.push("integer"),
+            (_, true) => names.push("number"),
+            (false, false) => {}
+        }
+        if self.strings.is_some() {
+            names.push("string");
+        }
+        if self.items.is_some() {
+            names.push("array");
+        }
+        if self.objects.is_some() {
+            names.push("object");
+        }
+        if self.null {
+            names.push("null");
+        }
+        names
+    }
+
+    /// Whether the strings are listed as an `enum`, which would reject samples of other types
+    fn enumerated(&self) -> bool {
+        match &self.strings {
+            Some(Strings {
+                count,
+                values: Some(values),
+                ..
+            }) => values.len() < *count,
+            _ => false,
+        }
+    }
+
+    /// Adds the keywords of each type that was seen to `builder`
+    fn keywords(self, mut builder: SchemaBuilder) -> SchemaBuilder {
+        let enumerated = self.enumerated();
+        if let Some(strings) = self.strings {
+            if let Some(format) = strings.formats.first() {
+                builder = builder.format(*format);
+            }
+            if let (true, Some(values)) = (enumerated, strings.values) {
+                builder = builder.enum_values(values);
+            }
+        }
+        if let Some(items) = self.items {
+            builder = builder.items(items.into_property());
+        }
+        if let Some(objects) = self.objects {
+            let mut required = Vec::new();
+            for (name, (count, shape)) in objects.properties {
+                if count == objects.count {
+                    required.push(name.clone());
+                }
+                builder = builder.property(name, shape.into_property());
+            }
+            // without `preserve_order` the properties come in no particular order
+            #[cfg(not(feature = "preserve_order"))]
+            required.sort();
+            if !required.is_empty() {
+                builder = builder.required(required);
+            }
+        }
+        builder
+    }
+
+    fn into_builder(mut self) -> SchemaBuilder {
+        let names = self.names();
+        if names.len() > 1 && self.enumerated() {
+            let strings = Shape {
+                strings: self.strings.take(),
+                ..Shape::default()
+            };
+            return Schema::any()
+                .any_of(self.into_property())
+                .any_of(strings.into_property());
+        }
+        let builder = match names[..] {
+            // there were no samples, e.g. only empty arrays
+            [] => return SchemaBuilder::from(true),
+            [name] => typed(name),
+            _ => SchemaBuilder::from(Property::Any(AnyProperty {
+                keywords: UntypedKeywords {
+                    types: Some(Types::Several(
+                        names.into_iter().map(String::from).collect(),
+                    )),
+                    ..UntypedKeywords::default()
+                },
+                ..AnyProperty::default()
+            })),
+        };
+        self.keywords(builder)
+    }
+
+    fn into_property(self) -> Property {
+        self.into_builder().into()
+    }
+}
+
+/// Starts a subschema of the single type `name`
+fn typed(name: &str) -> SchemaBuilder {
+    match name {
+        "null" => Schema::null(),
+        "boolean" => Schema::boolean(),
+        "integer" => Schema::integer(),
+        "number" => Schema::number(),
+        "string" => Schema::string(),
+        "array" => Schema::array(),
+        _ => Schema::object(),
+    }
+}
+
+impl Schema {
+    /// Derives a schema that accepts all of the `samples`.
+    ///
+    /// Every property that all objects have is `required`, integers are told apart from
+    /// other numbers and samples of different types share a list of types, e.g.
+    /// `["string", "null"]` for nullable ones.
+    /// Without any samples this is `true`.
+    pub fn infer(samples: &[Value]) -> Schema {
+        Schema::infer_with(samples, &InferenceOptions::default())
+    }
+
+    /// Like [`Schema::infer`], but also looks for formats or enumerations if `options` say so
+    pub fn infer_with(samples: &[Value], options: &InferenceOptions) -> Schema {
+        let mut shape = Shape::default();
+        for sample in samples {
+            shape.add(sample, options);
+        }
+        shape.into_builder().build()
+    }
+}
@@ -0,0 +1,62 @@
+//! User defined keywords that take part in validation
+//!
+//! ```
+//! use serde_json::{json, Value};
+//! use serde_json_schema::{error::{Error, Result}, keyword::Keyword, Schema, TryFrom, ValidationOptions};
+//!
+//! /// `"x-max-bytes": 4` limits the length of a string in bytes
+//! struct MaxBytes(usize);
+//!
+//! impl Keyword for MaxBytes {
+//!     fn compile(value: &Value) -> Result<Self> {
+//!         match value.as_u64() {
+//!             Some(max) => Ok(MaxBytes(max as usize)),
+//!             None => Err(Error::keyword("expected a positive integer")),
+//!         }
+//!     }
+//!
+//!     fn validate(&self, instance: &Value) -> Result<()> {
+//!         match instance.as_str() {
+//!             Some(s) if s.len() > self.0 => Err(Error::keyword(format!(
+//!                 "{:?} is longer than {} bytes",
+//!                 s, self.0
+//!             ))),
+//!             _ => Ok(()),
+//!         }
+//!     }
+//! }
+//!
+//! let schema = Schema::try_from(json!({"type": "string", "x-max-bytes": 4})).unwrap();
+//! let options = ValidationOptions::new().keyword::<MaxBytes>("x-max-bytes");
+//! assert!(schema.validate_with(&json!("äbc"), &options).is_ok());
+//! assert!(schema.validate_with(&json!("äbcd"), &options).is_err());
+//! ```
+
+use serde_json::Value;
+
+use std::sync::Arc;
+
+use crate::error::Result;
+
+/// A keyword that this crate does not model, but that should be validated.
+///
+/// Register it with [`ValidationOptions::keyword`](crate::ValidationOptions::keyword).
+pub trait Keyword: Send + Sync {
+    /// Prepares the keyword from the value it has in the schema
+    fn compile(value: &Value) -> Result<Self>
+    where
+        Self: Sized;
+
+    /// Checks the instance location that the subschema containing this keyword applies to
+    fn validate(&self, instance: &Value) -> Result<()>;
+}
+
+/// Compiles a registered [`Keyword`] from its value
+pub(crate) type KeywordCompiler = Arc<dyn Fn(&Value) -> Result<Arc<dyn Keyword>> + Send + Sync>;
+
+pub(crate) fn compiler<K: Keyword + 'static>() -> KeywordCompiler {
+    Arc::new(|value| {
+        let keyword: Arc<dyn Keyword> = Arc::new(K::compile(value)?);
+        Ok(keyword)
+    })
+}
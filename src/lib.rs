@@ -29,27 +29,83 @@
 use serde::{Deserialize, Serialize};
 pub use url::Url;
 
-use std::collections::HashMap;
 pub use std::convert::TryFrom;
 
+pub mod annotation;
+pub mod applicator;
+pub mod builder;
+mod bundle;
+mod content;
+mod dereference;
+pub mod diff;
+pub mod draft;
 pub mod error;
+pub mod format;
 pub mod id;
+mod infer;
+pub mod keyword;
+mod merge;
+mod normalize;
+mod pointer;
 pub mod property;
+pub mod resolver;
+pub mod satisfiability;
+mod subset;
 mod validation;
+pub mod visit;
 
+use crate::annotation::{AnnotationMap, Annotations};
+use crate::draft::Draft;
 use crate::error::Result;
 use crate::id::*;
 use crate::property::*;
+use crate::resolver::Resolver;
+
+pub use crate::diff::schema_diff;
+pub use crate::infer::InferenceOptions;
+pub use crate::subset::Compatibility;
+pub use crate::validation::{RegexEngine, ValidationOptions};
+
+/// The map used for `properties`, `definitions` and `dependencies`.
+///
+/// With the `preserve_order` feature this keeps the order of the document,
+/// so that serializing a schema does not reorder it.
+#[cfg(feature = "preserve_order")]
+pub type SchemaMap<V> = indexmap::IndexMap<String, V>;
+
+/// The map used for `properties`, `definitions` and `dependencies`.
+///
+/// With the `preserve_order` feature this keeps the order of the document,
+/// so that serializing a schema does not reorder it.
+#[cfg(not(feature = "preserve_order"))]
+pub type SchemaMap<V> = std::collections::HashMap<String, V>;
 
 /// Represents a full JSON Schema Document
 // TODO: root array vs object
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, PartialEq)]
 pub struct Schema(SchemaInner);
 
+/// A document that was edited down to just `true` or `false` is written as such
+impl Serialize for Schema {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match &self.0 {
+            SchemaInner::Schema(definition) => match definition.as_boolean() {
+                Some(value) => value.serialize(serializer),
+                None => definition.serialize(serializer),
+            },
+            SchemaInner::Boolean(value) => value.serialize(serializer),
+        }
+    }
+}
+
 /// Represents a full JSON Schema Document
 // TODO: root array vs object
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 enum SchemaInner {
     /// The Common case
     Schema(SchemaDefinition),
@@ -70,6 +126,11 @@ impl Schema {
         }
     }
 
+    /// The specification version declared by `$schema`, if it is a known one
+    pub fn draft(&self) -> Option<Draft> {
+        self.schema().and_then(Draft::from_url)
+    }
+
     fn as_definition(&self) -> Option<&SchemaDefinition> {
         match &self.0 {
             SchemaInner::Schema(definition @ SchemaDefinition { .. }) => Some(definition),
@@ -85,26 +146,83 @@ impl Schema {
         self.as_definition().and_then(|d| d.schema.as_ref())
     }
 
+    /// Title, description and other keywords that only describe the root schema
+    pub fn annotations(&self) -> Option<&Annotations> {
+        self.as_property().map(Property::annotations)
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.annotations().and_then(|a| a.title.as_deref())
+    }
+
     pub fn description(&self) -> Option<&str> {
-        self.as_definition().and_then(|d| d.description.as_deref())
+        self.annotations().and_then(|a| a.description.as_deref())
     }
 
-    pub fn specification(&self) -> Option<&PropertyInstance> {
+    pub fn default(&self) -> Option<&serde_json::Value> {
+        self.annotations().and_then(|a| a.default.as_ref())
+    }
+
+    pub fn examples(&self) -> &[serde_json::Value] {
+        self.annotations()
+            .and_then(|a| a.examples.as_deref())
+            .unwrap_or_default()
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.annotations()
+            .and_then(|a| a.read_only)
+            .unwrap_or(false)
+    }
+
+    pub fn write_only(&self) -> bool {
+        self.annotations()
+            .and_then(|a| a.write_only)
+            .unwrap_or(false)
+    }
+
+    pub fn deprecated(&self) -> bool {
+        self.annotations()
+            .and_then(|a| a.deprecated)
+            .unwrap_or(false)
+    }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.annotations().and_then(|a| a.comment.as_deref())
+    }
+
+    /// Keywords of the root schema that this crate does not model, such as `x-*` extensions
+    pub fn extra(&self) -> Option<&serde_json::Map<String, serde_json::Value>> {
+        self.as_property().map(Property::extra)
+    }
+
+    /// Looks up a keyword of the root schema that this crate does not model by its name
+    pub fn extra_keyword(&self, name: &str) -> Option<&serde_json::Value> {
+        self.extra().and_then(|extra| extra.get(name))
+    }
+
+    /// The root schema as a subschema, so that it can be treated like any nested one.
+    ///
+    /// `None` only for a document without any keywords besides `$id`, `$schema` and definitions.
+    pub fn as_property(&self) -> Option<&Property> {
+        static TRUE: Property = Property::Boolean(true);
+        static FALSE: Property = Property::Boolean(false);
         match &self.0 {
-            SchemaInner::Schema(SchemaDefinition {
-                specification:
-                    Some(Property::Value(specification @ PropertyInstance::Object { .. })),
-                ..
-            }) => Some(specification),
-            SchemaInner::Schema(SchemaDefinition {
-                specification: Some(Property::Value(specification @ PropertyInstance::Array { .. })),
-                ..
-            }) => Some(specification),
+            SchemaInner::Schema(definition) => definition.specification.as_ref(),
+            SchemaInner::Boolean(true) => Some(&TRUE),
+            SchemaInner::Boolean(false) => Some(&FALSE),
+        }
+    }
+
+    /// The root schema, if it has a `type`
+    pub fn specification(&self) -> Option<&PropertyInstance> {
+        match self.as_property() {
+            Some(Property::Value(specification)) => Some(specification),
             _ => None,
         }
     }
 
-    pub fn properties(&self) -> Option<&HashMap<String, Property>> {
+    pub fn properties(&self) -> Option<&SchemaMap<Property>> {
         match self.specification() {
             Some(PropertyInstance::Object { properties, .. }) => Some(properties),
             _ => None,
@@ -120,14 +238,14 @@ impl Schema {
 
     pub fn as_null(&self) -> Option<&PropertyInstance> {
         match self.specification() {
-            Some(null @ PropertyInstance::Null) => Some(null),
+            Some(null @ PropertyInstance::Null { .. }) => Some(null),
             _ => None,
         }
     }
 
     pub fn as_boolean(&self) -> Option<&PropertyInstance> {
         match self.specification() {
-            Some(boolean @ PropertyInstance::Boolean) => Some(boolean),
+            Some(boolean @ PropertyInstance::Boolean { .. }) => Some(boolean),
             _ => None,
         }
     }
@@ -160,78 +278,427 @@ impl Schema {
         }
     }
 
+    pub fn as_string(&self) -> Option<&PropertyInstance> {
+        match self.specification() {
+            Some(string @ PropertyInstance::String { .. }) => Some(string),
+            _ => None,
+        }
+    }
+
+    fn as_definition_mut(&mut self) -> &mut SchemaDefinition {
+        if let SchemaInner::Boolean(value) = self.0 {
+            self.0 = SchemaInner::Schema(SchemaDefinition::boolean(value));
+        }
+        match &mut self.0 {
+            SchemaInner::Schema(definition) => definition,
+            SchemaInner::Boolean(_) => unreachable!("booleans were replaced by a definition"),
+        }
+    }
+
+    pub fn set_id(&mut self, id: impl Into<SchemaId>) {
+        self.as_definition_mut().id = Some(id.into());
+    }
+
+    pub fn set_schema(&mut self, schema: Url) {
+        self.as_definition_mut().schema = Some(schema);
+    }
+
+    /// The root schema as a subschema that can be edited in place.
+    ///
+    /// A document without any keywords besides `$id`, `$schema` and definitions gets an empty one.
+    pub fn as_property_mut(&mut self) -> &mut Property {
+        self.as_definition_mut()
+            .specification
+            .get_or_insert_with(|| Property::Any(AnyProperty::default()))
+    }
+
+    /// The root schema, if it has a `type`
+    pub fn specification_mut(&mut self) -> Option<&mut PropertyInstance> {
+        match self.as_property_mut() {
+            Property::Value(specification) => Some(specification),
+            _ => None,
+        }
+    }
+
+    /// Title, description and other keywords that only describe the root schema.
+    ///
+    /// A root schema that is `true` or `false` becomes `{}` or `{"not": {}}` to carry them.
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        self.as_property_mut().annotations_mut()
+    }
+
+    pub fn set_title(&mut self, title: impl Into<String>) {
+        self.annotations_mut().title = Some(title.into());
+    }
+
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.annotations_mut().description = Some(description.into());
+    }
+
+    pub fn properties_mut(&mut self) -> Option<&mut SchemaMap<Property>> {
+        match self.specification_mut() {
+            Some(PropertyInstance::Object { properties, .. }) => Some(properties),
+            _ => None,
+        }
+    }
+
+    /// The `required` properties of an object, which are added if there are none yet
+    pub fn required_properties_mut(&mut self) -> Option<&mut Vec<String>> {
+        match self.specification_mut() {
+            Some(PropertyInstance::Object { required, .. }) => {
+                Some(required.get_or_insert_with(Vec::new))
+            }
+            _ => None,
+        }
+    }
+
+    /// The `$defs` of the document, or its `definitions` if it has no `$defs`
+    pub fn definitions(&self) -> Option<&SchemaMap<SchemaDefinition>> {
+        self.as_definition()
+            .and_then(|d| d.defs.as_ref().or(d.definitions.as_ref()))
+    }
+
+    /// Like [`Schema::definitions`], which are added if there are none yet.
+    ///
+    /// New definitions go under `$defs` if the document declares 2019-09 or later, under
+    /// `definitions` otherwise.
+    pub fn definitions_mut(&mut self) -> &mut SchemaMap<SchemaDefinition> {
+        let modern = self
+            .draft()
+            .is_some_and(|draft| draft >= Draft::Draft201909);
+        let definition = self.as_definition_mut();
+        if definition.defs.is_some() || (modern && definition.definitions.is_none()) {
+            definition.defs.get_or_insert_with(SchemaMap::default)
+        } else {
+            definition
+                .definitions
+                .get_or_insert_with(SchemaMap::default)
+        }
+    }
+
+    /// Adds `subschema` at `pointer`, e.g. `/properties/name`, `/$defs/address` or `/anyOf/-`.
+    ///
+    /// This follows the `add` operation of JSON Patch: what is there already is replaced, elements
+    /// of `allOf` and the like are shifted back. The object the subschema is added to is created if
+    /// it is missing, anything further up has to exist.
+    pub fn insert(&mut self, pointer: &str, subschema: impl Into<Property>) -> Result<()> {
+        let mut document = serde_json::to_value(&*self)?;
+        pointer::insert(
+            &mut document,
+            pointer,
+            serde_json::to_value(subschema.into())?,
+        )?;
+        *self = Schema::try_from(document)?;
+        Ok(())
+    }
+
+    /// Removes the subschema at `pointer` and returns it, e.g. `/properties/name`.
+    ///
+    /// Fails with [`ErrorKind::InvalidPointer`](error::ErrorKind::InvalidPointer) if there is
+    /// no subschema at `pointer`.
+    pub fn remove(&mut self, pointer: &str) -> Result<Property> {
+        let mut document = serde_json::to_value(&*self)?;
+        let removed = pointer::remove(&mut document, pointer)?;
+        let removed = serde_json::from_value(removed)
+            .map_err(|_| error::ErrorKind::InvalidPointer(pointer.to_owned()))?;
+        *self = Schema::try_from(document)?;
+        Ok(removed)
+    }
+
+    /// Produces a single self-contained document, with every external `$ref` target embedded.
+    ///
+    /// Since 2019-09 the referenced documents are embedded under `$defs` and keep their `$id`,
+    /// so references to them stay valid.
+    /// For older drafts they are inlined under `definitions` without their `$id` and all references
+    /// into them are rewritten to point there instead.
+    /// Documents without `$schema` are bundled like the latest draft.
+    pub fn bundle(&self, resolver: &dyn Resolver) -> Result<Schema> {
+        bundle::bundle(self, resolver)
+    }
+
+    /// Replaces every `$ref` with a copy of the subschema it points at.
+    ///
+    /// References that point back at one of their own ancestors can't be inlined, these are kept
+    /// as they are. Use [`Schema::dereferenced_with`] to treat them as an error instead.
+    /// Keywords next to a `$ref` end up in an `allOf` with its target, unless the draft of the
+    /// document ignores them.
+    /// External references have to be [bundled](Schema::bundle) first.
+    pub fn dereferenced(&self) -> Result<Schema> {
+        self.dereferenced_with(RecursiveRefs::Keep)
+    }
+
+    /// Like [`Schema::dereferenced`], but decides what happens to recursive references.
+    pub fn dereferenced_with(&self, recursive: RecursiveRefs) -> Result<Schema> {
+        dereference::dereferenced(self, recursive)
+    }
+
     pub fn validate(&self, json: &serde_json::Value) -> std::result::Result<(), Vec<String>> {
-        match self.0 {
-            SchemaInner::Schema(SchemaDefinition {
-                specification: Some(Property::Value(ref prop)),
-                ..
-            }) => prop.validate(json),
-            SchemaInner::Schema(SchemaDefinition {
-                specification: Some(Property::Ref(_)),
-                ..
-            }) => unimplemented!(),
-            SchemaInner::Boolean(true) => {
-                eprintln!(r#"your schema is just "true", everything goes"#);
-                Ok(())
+        self.validate_with(json, &ValidationOptions::default())
+    }
+
+    /// Like [`Schema::validate`], but configured by `options`
+    pub fn validate_with(
+        &self,
+        json: &serde_json::Value,
+        options: &ValidationOptions,
+    ) -> std::result::Result<(), Vec<String>> {
+        self.with_resolver(options, |schema| {
+            schema.validate_in(json, &mut validation::Context::new(Some(schema), options))
+        })
+        .map_err(|mut errors| {
+            if let Some(limit) = options.error_limit() {
+                errors.truncate(limit);
             }
-            SchemaInner::Boolean(false) => Err(vec![String::from(
-                r#""the scheme "false" will never validate"#,
-            )]),
-            _ => Ok(()),
+            errors
+        })
+    }
+
+    /// Validates `json` and returns the annotations that apply to it.
+    ///
+    /// These tell e.g. which parts of the instance are `deprecated` or `readOnly`, keyed by their
+    /// JSON Pointer.
+    pub fn annotate(
+        &self,
+        json: &serde_json::Value,
+    ) -> std::result::Result<AnnotationMap, Vec<String>> {
+        self.annotate_with(json, &ValidationOptions::default())
+    }
+
+    /// Like [`Schema::annotate`], but configured by `options`
+    pub fn annotate_with(
+        &self,
+        json: &serde_json::Value,
+        options: &ValidationOptions,
+    ) -> std::result::Result<AnnotationMap, Vec<String>> {
+        self.with_resolver(options, |schema| {
+            let mut context = validation::Context::annotating(Some(schema), options);
+            schema.validate_in(json, &mut context)?;
+            Ok(context.into_annotations())
+        })
+    }
+
+    /// Runs `f` on this schema, or on its bundle if `options` can resolve external references
+    fn with_resolver<T>(
+        &self,
+        options: &ValidationOptions,
+        f: impl FnOnce(&Schema) -> std::result::Result<T, Vec<String>>,
+    ) -> std::result::Result<T, Vec<String>> {
+        match options.external_resolver() {
+            Some(resolver) => f(&self.bundle(resolver).map_err(|e| vec![e.to_string()])?),
+            None => f(self),
+        }
+    }
+
+    fn validate_in(
+        &self,
+        json: &serde_json::Value,
+        context: &mut validation::Context,
+    ) -> std::result::Result<(), Vec<String>> {
+        if let SchemaInner::Boolean(true) = self.0 {
+            log::debug!(r#"the schema is just "true", everything goes"#);
+        }
+        match self.as_property() {
+            Some(property) => property.validate_in(json, context).map(drop),
+            None => Ok(()),
         }
     }
 }
 
 impl TryFrom<serde_json::Value> for Schema {
-    type Error = crate::error::Error;
+    type Error = error::Error;
     fn try_from(v: serde_json::Value) -> Result<Schema> {
         Ok(serde_json::from_value(v)?)
     }
 }
 
 impl TryFrom<&str> for Schema {
-    type Error = crate::error::Error;
+    type Error = error::Error;
     fn try_from(s: &str) -> Result<Schema> {
         Ok(serde_json::from_str(s)?)
     }
 }
 
 impl TryFrom<String> for Schema {
-    type Error = crate::error::Error;
+    type Error = error::Error;
     fn try_from(s: String) -> Result<Schema> {
         Ok(serde_json::from_str(&s)?)
     }
 }
 
 impl TryFrom<&str> for SchemaDefinition {
-    type Error = crate::error::Error;
+    type Error = error::Error;
     fn try_from(s: &str) -> Result<SchemaDefinition> {
         Ok(serde_json::from_str(s)?)
     }
 }
 
 impl TryFrom<String> for SchemaDefinition {
-    type Error = crate::error::Error;
+    type Error = error::Error;
     fn try_from(s: String) -> Result<SchemaDefinition> {
         Ok(serde_json::from_str(&s)?)
     }
 }
 
-/// Represents a full JSON Schema Document, except when it is a boolean
+/// Represents a full JSON Schema Document, except when it is a boolean.
+///
+/// Definitions are documents of their own, they may carry an `$id` and further definitions.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-pub(crate) struct SchemaDefinition {
-    #[serde(rename = "$id")]
+pub struct SchemaDefinition {
+    #[serde(rename = "$id", skip_serializing_if = "Option::is_none")]
     pub id: Option<SchemaId>,
 
-    #[serde(rename = "$schema")]
+    #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
     pub schema: Option<Url>,
-    pub description: Option<String>,
-    // pub properties: HashMap<String, Property>,
-    pub dependencies: Option<HashMap<String, Vec<String>>>,
+    // pub properties: SchemaMap<Property>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<SchemaMap<Vec<String>>>,
 
-    #[serde(flatten)]
+    #[serde(flatten, serialize_with = "serialize_specification")]
     pub specification: Option<Property>,
 
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub definitions: Option<HashMap<String, SchemaDefinition>>,
+    #[serde(
+        default,
+        deserialize_with = "deserialize_definitions",
+        serialize_with = "serialize_definitions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub definitions: Option<SchemaMap<SchemaDefinition>>,
+
+    #[serde(
+        rename = "$defs",
+        default,
+        deserialize_with = "deserialize_definitions",
+        serialize_with = "serialize_definitions",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub defs: Option<SchemaMap<SchemaDefinition>>,
+}
+
+/// A definition as it appears in the document, `true` and `false` are valid definitions too
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum DefinitionRepr<D> {
+    Boolean(bool),
+    Definition(D),
+}
+
+/// Keeps boolean definitions as [`Property::Boolean`], so that they can be referenced like any other
+fn deserialize_definitions<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Option<SchemaMap<SchemaDefinition>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let definitions = SchemaMap::<DefinitionRepr<SchemaDefinition>>::deserialize(deserializer)?;
+    Ok(Some(
+        definitions
+            .into_iter()
+            .map(|(name, definition)| match definition {
+                DefinitionRepr::Boolean(value) => (name, SchemaDefinition::boolean(value)),
+                DefinitionRepr::Definition(definition) => (name, definition),
+            })
+            .collect(),
+    ))
+}
+
+/// `true` and `false` can't be flattened, next to `$id` and the like they are written as `{}` or `{"not": {}}`
+fn serialize_specification<S>(
+    specification: &Option<Property>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    match specification {
+        Some(property @ Property::Boolean(_)) => {
+            let mut property = property.clone();
+            property.expand_boolean();
+            property.serialize(serializer)
+        }
+        _ => specification.serialize(serializer),
+    }
+}
+
+fn serialize_definitions<S>(
+    definitions: &Option<SchemaMap<SchemaDefinition>>,
+    serializer: S,
+) -> std::result::Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeMap;
+
+    let definitions = definitions.iter().flatten();
+    let mut map = serializer.serialize_map(None)?;
+    for (name, definition) in definitions {
+        match definition.as_boolean() {
+            Some(value) => map.serialize_entry(name, &DefinitionRepr::<()>::Boolean(value))?,
+            None => map.serialize_entry(name, definition)?,
+        }
+    }
+    map.end()
+}
+
+impl SchemaDefinition {
+    /// The definition `true` or `false`
+    pub fn boolean(value: bool) -> SchemaDefinition {
+        SchemaDefinition {
+            id: None,
+            schema: None,
+            dependencies: None,
+            specification: Some(Property::Boolean(value)),
+            definitions: None,
+            defs: None,
+        }
+    }
+
+    /// `Some` if this is the definition `true` or `false`
+    pub fn as_boolean(&self) -> Option<bool> {
+        match self {
+            SchemaDefinition {
+                id: None,
+                schema: None,
+                dependencies: None,
+                specification: Some(Property::Boolean(value)),
+                definitions: None,
+                defs: None,
+            } => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// `definitions` and `$defs`
+    pub(crate) fn subschemas(&self) -> impl Iterator<Item = &SchemaDefinition> {
+        self.definitions
+            .iter()
+            .chain(self.defs.iter())
+            .flat_map(SchemaMap::values)
+    }
+
+    /// Finds an embedded schema resource by its `$id`
+    pub(crate) fn find_resource(&self, url: &Url) -> Option<&SchemaDefinition> {
+        self.subschemas()
+            .find_map(|definition| match &definition.id {
+                Some(SchemaId::Url(id)) if id == url => Some(definition),
+                _ => definition.find_resource(url),
+            })
+    }
+
+    pub(crate) fn try_for_each_ref_mut<E>(
+        &mut self,
+        f: &mut impl FnMut(&mut RefProperty) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        if let Some(specification) = self.specification.as_mut() {
+            specification.try_for_each_ref_mut(f)?;
+        }
+        for definition in self
+            .definitions
+            .iter_mut()
+            .chain(self.defs.iter_mut())
+            .flat_map(SchemaMap::values_mut)
+        {
+            definition.try_for_each_ref_mut(f)?;
+        }
+        Ok(())
+    }
 }
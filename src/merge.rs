@@ -0,0 +1,430 @@
+//! Merging the subschemas of `allOf` into their parent
+
+use serde_json::{Map, Value};
+
+use std::convert::TryFrom;
+
+use crate::draft::Draft;
+use crate::error::{ErrorKind, Result};
+use crate::normalize::{SUBSCHEMA, SUBSCHEMA_LISTS, SUBSCHEMA_MAPS};
+use crate::property::{Property, Types};
+use crate::satisfiability::{reason, Reason};
+use crate::subset::{intersect_types, types, LOWER_BOUNDS, UPPER_BOUNDS};
+use crate::validation::equal;
+use crate::Schema;
+
+/// Keywords that don't change what is valid, the first value is kept
+const ANNOTATIONS: &[&str] = &[
+    "$comment",
+    "contentEncoding",
+    "contentMediaType",
+    "default",
+    "deprecated",
+    "description",
+    "examples",
+    "readOnly",
+    "title",
+    "writeOnly",
+];
+
+/// Keywords that tie a subschema to where it is found, branches with these are not merged
+const LOCAL: &[&str] = &[
+    "$anchor",
+    "$defs",
+    "$dynamicAnchor",
+    "$id",
+    "$recursiveAnchor",
+    "$schema",
+    "definitions",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// Why two subschemas were not merged
+enum Clash {
+    /// No instance satisfies both, because of this keyword
+    Conflict(String),
+    /// Both have to stay apart, e.g. two different `pattern`s
+    Unsupported,
+}
+
+type Merged = std::result::Result<Map<String, Value>, Clash>;
+
+fn child(pointer: &str, segment: &str) -> String {
+    format!(
+        "{}/{}",
+        pointer,
+        segment.replace('~', "~0").replace('/', "~1")
+    )
+}
+
+struct Merger {
+    document: Value,
+    /// The `$ref`s that are currently being inlined
+    following: Vec<String>,
+    /// Whether keywords next to `$ref` apply, which they do since 2019-09
+    siblings: bool,
+}
+
+impl Merger {
+    fn subschema(&mut self, schema: &mut Value, pointer: &str) -> Result<()> {
+        let keywords = match schema {
+            Value::Object(keywords) => keywords,
+            _ => return Ok(()),
+        };
+        for (keyword, value) in keywords.iter_mut() {
+            let at = child(pointer, keyword);
+            match value {
+                Value::Array(list) if SUBSCHEMA_LISTS.contains(&keyword.as_str()) => {
+                    for (index, subschema) in list.iter_mut().enumerate() {
+                        self.subschema(subschema, &child(&at, &index.to_string()))?;
+                    }
+                }
+                Value::Object(map) if SUBSCHEMA_MAPS.contains(&keyword.as_str()) => {
+                    for (name, subschema) in map.iter_mut() {
+                        self.subschema(subschema, &child(&at, name))?;
+                    }
+                }
+                value if SUBSCHEMA.contains(&keyword.as_str()) => self.subschema(value, &at)?,
+                _ => {}
+            }
+        }
+
+        // merged branches would end up next to the `$ref`, where they are ignored
+        if keywords.contains_key("$ref") && !self.siblings {
+            return Ok(());
+        }
+        let branches = match keywords.remove("allOf") {
+            Some(Value::Array(branches)) => branches,
+            Some(other) => {
+                keywords.insert("allOf".into(), other);
+                return Ok(());
+            }
+            None => return Ok(()),
+        };
+        let conflict = |keyword: String| ErrorKind::Conflict {
+            pointer: pointer.to_owned(),
+            keyword,
+        };
+        let mut merged = keywords.clone();
+        let mut rest = Vec::new();
+        for branch in branches {
+            let resolved = match self.resolve(&branch)? {
+                Some(resolved) => resolved,
+                None => {
+                    rest.push(branch);
+                    continue;
+                }
+            };
+            match merge(merged.clone(), &resolved) {
+                Ok(result) => merged = result,
+                Err(Clash::Conflict(keyword)) => return Err(conflict(keyword).into()),
+                Err(Clash::Unsupported) => rest.push(branch),
+            }
+        }
+        satisfiable(&merged).map_err(conflict)?;
+        // what was left of the `allOf`s of the branches
+        if let Some(Value::Array(nested)) = merged.remove("allOf") {
+            rest.extend(nested);
+        }
+        if !rest.is_empty() {
+            merged.insert("allOf".into(), Value::Array(rest));
+        }
+        *keywords = merged;
+        Ok(())
+    }
+
+    /// The subschema a branch stands for, with the `allOf`s of a `$ref` target merged as well.
+    ///
+    /// `None` if it is better left as it is, e.g. for references to other documents.
+    fn resolve(&mut self, branch: &Value) -> Result<Option<Value>> {
+        let reference = match branch.get("$ref") {
+            Some(Value::String(reference)) => reference.clone(),
+            _ => return Ok(Some(branch.clone())),
+        };
+        let siblings = branch.as_object().map_or(0, |keywords| {
+            keywords
+                .keys()
+                .filter(|keyword| !ANNOTATIONS.contains(&keyword.as_str()))
+                .count()
+        });
+        let pointer = match reference.strip_prefix('#') {
+            Some(pointer) if siblings == 1 && !self.following.contains(&reference) => pointer,
+            _ => return Ok(None),
+        };
+        let mut target = match self.document.pointer(pointer) {
+            Some(target) => target.clone(),
+            None => return Ok(None),
+        };
+        self.following.push(reference.clone());
+        let merged = self.subschema(&mut target, pointer);
+        let resolved = merged.and_then(|_| self.resolve(&target));
+        self.following.pop();
+        resolved
+    }
+}
+
+/// What `keywords` requires of a member `name` without a property of its own, `None` if a pattern covers it
+fn member<'k>(
+    keywords: &'k Map<String, Value>,
+    name: &str,
+) -> std::result::Result<Option<&'k Value>, Clash> {
+    if let Some(Value::Object(patterns)) = keywords.get("patternProperties") {
+        for pattern in patterns.keys() {
+            let pattern = regex::Regex::new(pattern).map_err(|_| Clash::Unsupported)?;
+            if pattern.is_match(name) {
+                // the pattern still applies after merging
+                return Ok(None);
+            }
+        }
+    }
+    Ok(keywords.get("additionalProperties"))
+}
+
+/// Both subschemas in one, where a conflict only means that nothing may be there
+fn intersect(left: &Value, right: &Value) -> Value {
+    match (left, right) {
+        (Value::Bool(true), other) | (other, Value::Bool(true)) => other.clone(),
+        (Value::Bool(false), _) | (_, Value::Bool(false)) => Value::Bool(false),
+        (Value::Object(left), right) => match merge(left.clone(), right) {
+            Ok(merged) if satisfiable(&merged).is_ok() => Value::Object(merged),
+            Ok(_) | Err(Clash::Conflict(_)) => Value::Bool(false),
+            Err(Clash::Unsupported) => serde_json::json!({ "allOf": [left, right] }),
+        },
+        _ => serde_json::json!({ "allOf": [left, right] }),
+    }
+}
+
+/// The keywords of `keywords` and `other` that instances have to satisfy at once
+fn merge(mut keywords: Map<String, Value>, other: &Value) -> Merged {
+    let other = match other {
+        Value::Bool(true) => return Ok(keywords),
+        Value::Bool(false) => return Err(Clash::Conflict("allOf".into())),
+        Value::Object(other) => other,
+        _ => return Err(Clash::Unsupported),
+    };
+    if other
+        .keys()
+        .any(|keyword| LOCAL.contains(&keyword.as_str()))
+    {
+        return Err(Clash::Unsupported);
+    }
+
+    // the boolean `exclusiveMinimum` of draft 4 belongs to the `minimum` next to it
+    for [bound, exclusive] in [
+        ["minimum", "exclusiveMinimum"],
+        ["maximum", "exclusiveMaximum"],
+    ] {
+        let draft4 =
+            |keywords: &Map<String, Value>| keywords.get(exclusive).is_some_and(Value::is_boolean);
+        let bounded = |keywords: &Map<String, Value>| {
+            keywords.contains_key(bound) || keywords.contains_key(exclusive)
+        };
+        if (draft4(&keywords) || draft4(other)) && bounded(&keywords) && bounded(other) {
+            return Err(Clash::Unsupported);
+        }
+    }
+
+    properties(&mut keywords, other)?;
+    for (keyword, value) in other {
+        let existing = match keywords.get(keyword) {
+            None => {
+                keywords.insert(keyword.clone(), value.clone());
+                continue;
+            }
+            Some(existing) if existing == value => continue,
+            Some(existing) => existing,
+        };
+        let merged = match keyword.as_str() {
+            keyword if ANNOTATIONS.contains(&keyword) => continue,
+            "properties" | "patternProperties" | "additionalProperties" => continue,
+            "type" => {
+                let types = |value: &Value| serde_json::from_value::<Types>(value.clone()).ok();
+                let (left, right) = match (types(existing), types(value)) {
+                    (Some(left), Some(right)) => (left, right),
+                    _ => return Err(Clash::Unsupported),
+                };
+                let (left, right): (Vec<&str>, Vec<&str>) = (
+                    left.names().iter().map(String::as_str).collect(),
+                    right.names().iter().map(String::as_str).collect(),
+                );
+                let both = intersect_types(&left, &right);
+                match both.len() {
+                    0 => return Err(Clash::Conflict("type".into())),
+                    1 => Value::from(both[0]),
+                    _ => Value::from(both),
+                }
+            }
+            "enum" => match (existing, value) {
+                (Value::Array(left), Value::Array(right)) => Value::Array(
+                    left.iter()
+                        .filter(|value| right.iter().any(|other| equal(value, other)))
+                        .cloned()
+                        .collect(),
+                ),
+                _ => return Err(Clash::Unsupported),
+            },
+            "const" if equal(existing, value) => continue,
+            "const" => return Err(Clash::Conflict("const".into())),
+            "required" => match (existing, value) {
+                (Value::Array(left), Value::Array(right)) => {
+                    let mut both = left.clone();
+                    both.extend(right.iter().filter(|name| !left.contains(name)).cloned());
+                    Value::Array(both)
+                }
+                _ => return Err(Clash::Unsupported),
+            },
+            keyword if LOWER_BOUNDS.contains(&keyword) || UPPER_BOUNDS.contains(&keyword) => {
+                match (existing.as_f64(), value.as_f64()) {
+                    (Some(left), Some(right))
+                        if (left > right) == LOWER_BOUNDS.contains(&keyword) =>
+                    {
+                        existing.clone()
+                    }
+                    (Some(_), Some(_)) => value.clone(),
+                    _ => return Err(Clash::Unsupported),
+                }
+            }
+            "multipleOf" => match (existing.as_f64(), value.as_f64()) {
+                (Some(left), Some(right)) if (left / right).fract() == 0.0 => existing.clone(),
+                (Some(left), Some(right)) if (right / left).fract() == 0.0 => value.clone(),
+                _ => return Err(Clash::Unsupported),
+            },
+            "uniqueItems" => Value::Bool(true),
+            "items" if existing.is_array() || value.is_array() => return Err(Clash::Unsupported),
+            "items" | "propertyNames" => intersect(existing, value),
+            "allOf" => match (existing, value) {
+                (Value::Array(left), Value::Array(right)) => {
+                    Value::Array(left.iter().chain(right).cloned().collect())
+                }
+                _ => return Err(Clash::Unsupported),
+            },
+            // neither matches anything either does
+            "not" => serde_json::json!({ "anyOf": [existing, value] }),
+            _ => return Err(Clash::Unsupported),
+        };
+        keywords.insert(keyword.clone(), merged);
+    }
+    Ok(keywords)
+}
+
+/// Merges `properties`, `patternProperties` and `additionalProperties` of `other` into `keywords`
+fn properties(
+    keywords: &mut Map<String, Value>,
+    other: &Map<String, Value>,
+) -> std::result::Result<(), Clash> {
+    let object = |keywords: &Map<String, Value>, keyword: &str| match keywords.get(keyword) {
+        Some(Value::Object(map)) => map.clone(),
+        _ => Map::new(),
+    };
+    let (left_patterns, right_patterns) = (
+        object(keywords, "patternProperties"),
+        object(other, "patternProperties"),
+    );
+    // members that match a new pattern would escape the other `additionalProperties`
+    let escapes = |keywords: &Map<String, Value>,
+                   patterns: &Map<String, Value>,
+                   others: &Map<String, Value>| {
+        keywords.contains_key("additionalProperties")
+            && others.keys().any(|pattern| !patterns.contains_key(pattern))
+    };
+    if escapes(keywords, &left_patterns, &right_patterns)
+        || escapes(other, &right_patterns, &left_patterns)
+    {
+        return Err(Clash::Unsupported);
+    }
+
+    let (left, right) = (object(keywords, "properties"), object(other, "properties"));
+    let anything = Value::Bool(true);
+    let mut merged = Map::new();
+    for name in left.keys().chain(right.keys()) {
+        if merged.contains_key(name) {
+            continue;
+        }
+        let left = match left.get(name) {
+            Some(left) => Some(left),
+            None => member(keywords, name)?,
+        };
+        let right = match right.get(name) {
+            Some(right) => Some(right),
+            None => member(other, name)?,
+        };
+        let subschema = intersect(left.unwrap_or(&anything), right.unwrap_or(&anything));
+        merged.insert(name.clone(), subschema);
+    }
+
+    let mut patterns = left_patterns;
+    for (pattern, right) in right_patterns {
+        let subschema = match patterns.get(&pattern) {
+            Some(left) => intersect(left, &right),
+            None => right,
+        };
+        patterns.insert(pattern, subschema);
+    }
+
+    let additional = match (
+        keywords.get("additionalProperties"),
+        other.get("additionalProperties"),
+    ) {
+        (Some(left), Some(right)) => Some(intersect(left, right)),
+        (left, right) => left.or(right).cloned(),
+    };
+    for (keyword, value) in [
+        (
+            "properties",
+            (!merged.is_empty()).then_some(Value::Object(merged)),
+        ),
+        (
+            "patternProperties",
+            (!patterns.is_empty()).then_some(Value::Object(patterns)),
+        ),
+        ("additionalProperties", additional),
+    ] {
+        if let Some(value) = value {
+            keywords.insert(keyword.into(), value);
+        }
+    }
+    Ok(())
+}
+
+/// `Err` with the keyword that rules out every instance
+fn satisfiable(keywords: &Map<String, Value>) -> std::result::Result<(), String> {
+    let property: Property = match serde_json::from_value(Value::Object(keywords.clone())) {
+        Ok(property) => property,
+        Err(_) => return Ok(()),
+    };
+    let constraints = property.constraints();
+    let keyword = match reason(&constraints, types(&constraints)) {
+        None => return Ok(()),
+        Some(Reason::EmptyTypes) => "type",
+        Some(Reason::EmptyEnum) if keywords.contains_key("const") => "const",
+        Some(Reason::EmptyEnum) => "enum",
+        Some(Reason::EmptyRange { minimum, .. }) => minimum,
+        Some(Reason::ForbiddenProperty(_)) => "required",
+    };
+    Err(keyword.to_owned())
+}
+
+impl Schema {
+    /// Merges the subschemas of every `allOf` into the subschema it belongs to.
+    ///
+    /// `properties` and `required` are joined, the tightest bounds taken and `type`s and `enum`s
+    /// intersected. Local `$ref`s in `allOf` are replaced by what they point at.
+    /// An `allOf` next to a `$ref` is left as it is before 2019-09, which ignores both.
+    /// Branches that can't be merged, e.g. because of another `pattern`, are kept in a smaller
+    /// `allOf`.
+    ///
+    /// Fails with [`ErrorKind::Conflict`] if no instance satisfies all branches together, in which
+    /// case this schema is left as it is.
+    pub fn merge_all_of(&mut self) -> Result<()> {
+        let mut document = serde_json::to_value(&*self)?;
+        let mut merger = Merger {
+            document: document.clone(),
+            following: Vec::new(),
+            siblings: self.draft().is_none_or(|draft| draft >= Draft::Draft201909),
+        };
+        merger.subschema(&mut document, "")?;
+        *self = Schema::try_from(document)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,444 @@
+//! Rewriting a schema into a canonical form
+
+use serde_json::{Map, Value};
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+use crate::draft::Draft;
+use crate::subset::{allows_type, type_of};
+use crate::validation::equal;
+use crate::Schema;
+
+/// Keywords with a single subschema
+pub(crate) const SUBSCHEMA: &[&str] = &[
+    "additionalItems",
+    "additionalProperties",
+    "contains",
+    "contentSchema",
+    "else",
+    "if",
+    "items",
+    "not",
+    "propertyNames",
+    "then",
+    "unevaluatedItems",
+    "unevaluatedProperties",
+];
+
+/// Keywords with a subschema for each name
+pub(crate) const SUBSCHEMA_MAPS: &[&str] = &[
+    "$defs",
+    "definitions",
+    "dependencies",
+    "dependentSchemas",
+    "patternProperties",
+    "properties",
+];
+
+/// Keywords with a list of subschemas
+pub(crate) const SUBSCHEMA_LISTS: &[&str] = &["allOf", "anyOf", "oneOf", "items", "prefixItems"];
+
+/// Keywords whose value never rejects anything
+const TAUTOLOGIES: &[(&str, Value)] = &[
+    ("additionalItems", Value::Bool(true)),
+    ("additionalProperties", Value::Bool(true)),
+    ("items", Value::Bool(true)),
+    ("not", Value::Bool(false)),
+    ("propertyNames", Value::Bool(true)),
+    ("unevaluatedItems", Value::Bool(true)),
+    ("unevaluatedProperties", Value::Bool(true)),
+    ("uniqueItems", Value::Bool(false)),
+];
+
+/// Keywords that never reject anything when they are `0` or empty
+const EMPTY: &[&str] = &[
+    "$defs",
+    "allOf",
+    "definitions",
+    "dependencies",
+    "dependentRequired",
+    "dependentSchemas",
+    "minItems",
+    "minLength",
+    "minProperties",
+    "patternProperties",
+    "properties",
+    "required",
+];
+
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Array(values) => values.is_empty(),
+        Value::Object(values) => values.is_empty(),
+        Value::Number(number) => number.as_f64() == Some(0.0),
+        _ => false,
+    }
+}
+
+/// Sorts values by how they are written, for keywords where the order does not matter
+fn sort(values: &mut Vec<Value>) {
+    values.sort_by_cached_key(Value::to_string);
+    values.dedup();
+}
+
+/// Whether the document has an `unevaluatedProperties` or `unevaluatedItems`, which see the
+/// properties and items other keywords evaluated, even where those accept anything
+#[derive(Debug, Clone, Copy)]
+struct Unevaluated {
+    properties: bool,
+    items: bool,
+}
+
+impl Unevaluated {
+    fn of(document: &Value) -> Unevaluated {
+        fn mentions(value: &Value, keyword: &str) -> bool {
+            match value {
+                Value::Object(keywords) => {
+                    keywords.contains_key(keyword)
+                        || keywords.values().any(|value| mentions(value, keyword))
+                }
+                Value::Array(values) => values.iter().any(|value| mentions(value, keyword)),
+                _ => false,
+            }
+        }
+        Unevaluated {
+            properties: mentions(document, "unevaluatedProperties"),
+            items: mentions(document, "unevaluatedItems"),
+        }
+    }
+
+    /// Whether a keyword that accepts anything still has to be kept for its annotations
+    fn keeps(self, keyword: &str) -> bool {
+        match keyword {
+            "additionalProperties" => self.properties,
+            "items" | "additionalItems" => self.items,
+            "if" | "anyOf" => self.properties || self.items,
+            _ => false,
+        }
+    }
+}
+
+/// `siblings` tells whether keywords next to a `$ref` apply, which they do since 2019-09
+fn subschema(schema: &mut Value, unevaluated: Unevaluated, siblings: bool) {
+    let keywords = match schema {
+        Value::Object(keywords) => keywords,
+        _ => return,
+    };
+    // they are left as they are, apart from the definitions the `$ref` may point at
+    if keywords.contains_key("$ref") && !siblings {
+        for keyword in ["definitions", "$defs"] {
+            if let Some(Value::Object(definitions)) = keywords.get_mut(keyword) {
+                definitions
+                    .values_mut()
+                    .for_each(|value| subschema(value, unevaluated, siblings));
+            }
+        }
+        return;
+    }
+    for (keyword, value) in keywords.iter_mut() {
+        let keyword = keyword.as_str();
+        match value {
+            Value::Array(list) if SUBSCHEMA_LISTS.contains(&keyword) => list
+                .iter_mut()
+                .for_each(|value| subschema(value, unevaluated, siblings)),
+            Value::Object(map) if SUBSCHEMA_MAPS.contains(&keyword) => map
+                .values_mut()
+                .for_each(|value| subschema(value, unevaluated, siblings)),
+            value if SUBSCHEMA.contains(&keyword) => subschema(value, unevaluated, siblings),
+            _ => {}
+        }
+    }
+
+    types(keywords);
+    if !enumeration(keywords) {
+        return *schema = Value::Bool(false);
+    }
+    if let Some(Value::Array(required)) = keywords.get_mut("required") {
+        sort(required);
+    }
+    if !keywords.contains_key("if") {
+        keywords.remove("then");
+        keywords.remove("else");
+    } else if !keywords.contains_key("then")
+        && !keywords.contains_key("else")
+        && !unevaluated.keeps("if")
+    {
+        keywords.remove("if");
+    }
+    for keyword in ["allOf", "anyOf", "oneOf"] {
+        match combinator(keywords, keyword, unevaluated) {
+            Some(false) => return *schema = Value::Bool(false),
+            Some(true) => {
+                keywords.remove(keyword);
+            }
+            None => {}
+        }
+    }
+    keywords.retain(|keyword, value| {
+        !((TAUTOLOGIES.contains(&(keyword.as_str(), value.clone())) && !unevaluated.keeps(keyword))
+            || (EMPTY.contains(&keyword.as_str()) && is_empty(value)))
+    });
+    keywords.sort_keys();
+
+    if keywords.is_empty() {
+        *schema = Value::Bool(true);
+    }
+}
+
+/// Turns a `type` array into a single type where possible
+fn types(keywords: &mut Map<String, Value>) {
+    if let Some(Value::Array(names)) = keywords.get_mut("type") {
+        sort(names);
+        if names.contains(&Value::from("number")) {
+            names.retain(|name| name != "integer");
+        }
+        if names.len() == 1 {
+            let name = names.remove(0);
+            keywords.insert("type".into(), name);
+        }
+    }
+}
+
+/// Drops `enum` values the `type` rules out.
+///
+/// `false` if no value is left. The `type` stays, it rejects e.g. `1.0` for `"enum": [1]`.
+fn enumeration(keywords: &mut Map<String, Value>) -> bool {
+    let types: Option<Vec<String>> = match keywords.get("type") {
+        Some(Value::String(name)) => Some(vec![name.clone()]),
+        Some(Value::Array(names)) => names
+            .iter()
+            .map(|n| n.as_str().map(str::to_owned))
+            .collect(),
+        _ => None,
+    };
+    let allowed = |value: &Value| match &types {
+        Some(types) => {
+            let types: Vec<&str> = types.iter().map(String::as_str).collect();
+            allows_type(&types, type_of(value))
+        }
+        None => true,
+    };
+
+    if let (Some(constant), Some(Value::Array(values))) =
+        (keywords.get("const"), keywords.get("enum"))
+    {
+        if !values.iter().any(|value| equal(value, constant)) {
+            return false;
+        }
+        keywords.remove("enum");
+    }
+    if let Some(constant) = keywords.get("const") {
+        return allowed(constant);
+    }
+    let values = match keywords.get_mut("enum") {
+        Some(Value::Array(values)) => values,
+        _ => return true,
+    };
+    values.retain(|value| allowed(value));
+    sort(values);
+    !values.is_empty()
+}
+
+/// Flattens and simplifies `allOf`, `anyOf` or `oneOf`.
+///
+/// `Some(true)` if the keyword can be dropped, `Some(false)` if nothing can satisfy it.
+fn combinator(
+    keywords: &mut Map<String, Value>,
+    keyword: &str,
+    unevaluated: Unevaluated,
+) -> Option<bool> {
+    let mut branches = match keywords.remove(keyword) {
+        Some(Value::Array(branches)) => branches,
+        Some(other) => {
+            keywords.insert(keyword.into(), other);
+            return None;
+        }
+        None => return None,
+    };
+    match keyword {
+        "allOf" => {
+            if branches.contains(&Value::Bool(false)) {
+                return Some(false);
+            }
+            // nested `allOf`s apply to the same instance
+            let mut flat = Vec::new();
+            for branch in branches {
+                match branch {
+                    Value::Object(mut branch) if branch.contains_key("allOf") => {
+                        if let Some(Value::Array(nested)) = branch.remove("allOf") {
+                            flat.extend(nested);
+                        }
+                        if !branch.is_empty() {
+                            flat.push(Value::Object(branch));
+                        }
+                    }
+                    Value::Bool(true) => {}
+                    branch => flat.push(branch),
+                }
+            }
+            branches = flat;
+            sort(&mut branches);
+        }
+        "anyOf" => {
+            // the other branches still evaluate properties and items
+            if branches.contains(&Value::Bool(true)) && !unevaluated.keeps(keyword) {
+                return Some(true);
+            }
+            branches.retain(|branch| branch != &Value::Bool(false));
+            sort(&mut branches);
+        }
+        _ => {
+            branches.retain(|branch| branch != &Value::Bool(false));
+            // duplicates can't both be the only one that matches
+            branches.sort_by_cached_key(Value::to_string);
+        }
+    }
+
+    match branches.len() {
+        0 if keyword == "allOf" => return Some(true),
+        0 => return Some(false),
+        1 => {
+            if let Value::Object(branch) = &branches[0] {
+                if let Some(merged) = merge(keywords, branch) {
+                    *keywords = merged;
+                    return Some(true);
+                }
+            }
+        }
+        _ => {}
+    }
+    keywords.insert(keyword.into(), Value::Array(branches));
+    None
+}
+
+/// The keywords of both subschemas, `None` if they overlap or either is a `$ref` next to others
+fn merge(keywords: &Map<String, Value>, other: &Map<String, Value>) -> Option<Map<String, Value>> {
+    let refs = keywords.contains_key("$ref") || other.contains_key("$ref");
+    if refs && !keywords.is_empty() {
+        return None;
+    }
+    let mut merged = keywords.clone();
+    for (keyword, value) in other {
+        match merged.get(keyword) {
+            Some(existing) if existing != value => return None,
+            _ => {
+                merged.insert(keyword.clone(), value.clone());
+            }
+        }
+    }
+    Some(merged)
+}
+
+/// Every `$ref` below `value`
+fn references<'v>(value: &'v Value, found: &mut Vec<&'v str>) {
+    match value {
+        Value::Object(keywords) => {
+            for (keyword, value) in keywords {
+                match (keyword.as_str(), value) {
+                    ("$ref" | "$recursiveRef" | "$dynamicRef", Value::String(reference)) => {
+                        found.push(reference)
+                    }
+                    _ => references(value, found),
+                }
+            }
+        }
+        Value::Array(values) => values.iter().for_each(|value| references(value, found)),
+        _ => {}
+    }
+}
+
+/// Whether a definition could be referenced by something other than a JSON Pointer
+fn identified(value: &Value) -> bool {
+    match value {
+        Value::Object(keywords) => {
+            ["$id", "$anchor", "$dynamicAnchor", "$recursiveAnchor"]
+                .iter()
+                .any(|keyword| keywords.contains_key(*keyword))
+                || keywords.values().any(identified)
+        }
+        Value::Array(values) => values.iter().any(identified),
+        _ => false,
+    }
+}
+
+/// Removes `definitions` and `$defs` of the root that no `$ref` points at
+fn unused_definitions(document: &mut Value) {
+    let keywords = match document {
+        Value::Object(keywords) => keywords,
+        _ => return,
+    };
+    let mut rest = keywords.clone();
+    rest.remove("definitions");
+    rest.remove("$defs");
+    let rest = Value::Object(rest);
+    let mut pending = Vec::new();
+    references(&rest, &mut pending);
+
+    let mut used = BTreeSet::new();
+    while let Some(reference) = pending.pop() {
+        if reference == "#" {
+            continue;
+        }
+        let mut segments = match reference.strip_prefix("#/") {
+            Some(pointer) if !pointer.contains('%') => pointer.split('/'),
+            // e.g. an anchor or another document, which may lead anywhere
+            _ => return,
+        };
+        let keyword = match segments.next() {
+            Some("definitions") => "definitions",
+            Some("$defs") => "$defs",
+            _ => continue,
+        };
+        let name = match segments.next() {
+            Some(name) => name.replace("~1", "/").replace("~0", "~"),
+            None => continue,
+        };
+        if let Some(definition) = keywords.get(keyword).and_then(|d| d.get(&name)) {
+            if used.insert((keyword, name)) {
+                references(definition, &mut pending);
+            }
+        }
+    }
+
+    for keyword in ["definitions", "$defs"] {
+        if let Some(Value::Object(definitions)) = keywords.get_mut(keyword) {
+            definitions.retain(|name, definition| {
+                used.contains(&(keyword, name.clone())) || identified(definition)
+            });
+            if definitions.is_empty() {
+                keywords.remove(keyword);
+            }
+        }
+    }
+}
+
+impl Schema {
+    /// Rewrites this schema into a canonical form that accepts the same instances.
+    ///
+    /// Definitions that no `$ref` points at are dropped, nested `allOf`s flattened and single
+    /// subschemas of `allOf`, `anyOf` or `oneOf` merged into the parent.
+    /// `type` arrays with one entry become a single type, `enum` values the `type` rules out are
+    /// dropped, keywords that accept anything like `additionalProperties: true` are removed unless
+    /// an `unevaluatedProperties` or `unevaluatedItems` may see them, and keys, `required` and
+    /// `enum` values are sorted.
+    /// Before 2019-09 the keywords next to a `$ref` are ignored and left as they are.
+    /// Schemas that only differed in these ways compare equal afterwards.
+    pub fn normalize(&mut self) {
+        let mut document = match serde_json::to_value(&*self) {
+            Ok(document) => document,
+            Err(e) => return log::debug!("not normalizing: {}", e),
+        };
+        let unevaluated = Unevaluated::of(&document);
+        let siblings = self.draft().is_none_or(|draft| draft >= Draft::Draft201909);
+        subschema(&mut document, unevaluated, siblings);
+        unused_definitions(&mut document);
+        if document.as_object().is_some_and(Map::is_empty) {
+            document = Value::Bool(true);
+        }
+        match Schema::try_from(document) {
+            Ok(normalized) => *self = normalized,
+            Err(e) => log::debug!("not normalizing: {}", e),
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Editing a schema document at a [JSON Pointer](https://tools.ietf.org/html/rfc6901)
+
+use serde_json::{Map, Value};
+
+use crate::error::{ErrorKind, Result};
+
+fn invalid(pointer: &str) -> crate::error::Error {
+    ErrorKind::InvalidPointer(pointer.to_owned()).into()
+}
+
+/// The unescaped segments of `pointer`, split into its parent and the last one
+fn split(pointer: &str) -> Result<(Vec<String>, String)> {
+    let mut segments: Vec<String> = pointer
+        .strip_prefix('/')
+        .ok_or_else(|| invalid(pointer))?
+        .split('/')
+        .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+        .collect();
+    let last = segments.pop().ok_or_else(|| invalid(pointer))?;
+    Ok((segments, last))
+}
+
+/// The value under `segment`, for the last parent of an insertion a missing object is created
+fn child<'a>(parent: &'a mut Value, segment: &str, create: bool) -> Option<&'a mut Value> {
+    match parent {
+        Value::Object(object) => match create {
+            true => Some(
+                object
+                    .entry(segment)
+                    .or_insert_with(|| Value::Object(Map::new())),
+            ),
+            false => object.get_mut(segment),
+        },
+        Value::Array(array) => index(segment).and_then(move |index| array.get_mut(index)),
+        _ => None,
+    }
+}
+
+fn index(segment: &str) -> Option<usize> {
+    match segment.as_bytes() {
+        [b'0'] => Some(0),
+        [b'1'..=b'9', ..] => segment.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Adds `value` at `pointer` like a JSON Patch `add`, creating the object it is added to if only that is missing
+pub(crate) fn insert(document: &mut Value, pointer: &str, value: Value) -> Result<()> {
+    let (parents, last) = split(pointer)?;
+    let mut parent = document;
+    for (depth, segment) in parents.iter().enumerate() {
+        parent =
+            child(parent, segment, depth + 1 == parents.len()).ok_or_else(|| invalid(pointer))?;
+    }
+    match parent {
+        Value::Object(object) => {
+            object.insert(last, value);
+        }
+        Value::Array(array) if last == "-" => array.push(value),
+        Value::Array(array) => match index(&last) {
+            Some(index) if index <= array.len() => array.insert(index, value),
+            _ => return Err(invalid(pointer)),
+        },
+        _ => return Err(invalid(pointer)),
+    }
+    Ok(())
+}
+
+/// Takes the value at `pointer` out of `document`, keeping the order of what remains
+pub(crate) fn remove(document: &mut Value, pointer: &str) -> Result<Value> {
+    let (parents, last) = split(pointer)?;
+    let mut parent = document;
+    for segment in &parents {
+        parent = child(parent, segment, false).ok_or_else(|| invalid(pointer))?;
+    }
+    match parent {
+        #[cfg(feature = "preserve_order")]
+        Value::Object(object) => object.shift_remove(&last),
+        #[cfg(not(feature = "preserve_order"))]
+        Value::Object(object) => object.remove(&last),
+        Value::Array(array) => index(&last)
+            .filter(|index| *index < array.len())
+            .map(|index| array.remove(index)),
+        _ => None,
+    }
+    .ok_or_else(|| invalid(pointer))
+}
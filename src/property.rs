@@ -1,83 +1,990 @@
 //! Represents the [Instance Data Model](https://json-schema.org/latest/json-schema-core.html#rfc.section.4.2.1)
 
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
+use url::Url;
 
-use std::{collections::HashMap, str::Split};
+use std::sync::OnceLock;
 
-use crate::{validation::NumberCriteria, Schema};
+use crate::{
+    annotation::Annotations,
+    applicator::{Applicators, NO_APPLICATORS},
+    draft::Draft,
+    error::{ErrorKind, Result},
+    id::SchemaId,
+    validation::{
+        ArrayCriteria, Context, Evaluated, NumberCriteria, Outcome, Report, StringCriteria,
+        ValidationOptions, ValueCriteria,
+    },
+    Schema, SchemaDefinition, SchemaMap,
+};
 
-/// Either a `PropertyInstance` or a reference
+/// Either a `PropertyInstance`, a reference, a subschema without a `type` or `true`/`false`.
+///
+/// A subschema with a `$ref` is always a reference, a `type` next to it is one of its siblings.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(untagged)]
 pub enum Property {
-    Value(PropertyInstance),
     Ref(RefProperty),
+    Value(PropertyInstance),
+    Any(AnyProperty),
+    /// `true` accepts everything, `false` nothing
+    Boolean(bool),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct RefProperty {
     #[serde(rename = "$ref")]
     pub reference: String,
+
+    #[serde(flatten)]
+    pub annotations: Annotations,
+
+    #[serde(flatten)]
+    pub applicators: Applicators,
+
+    /// `type`, `properties` and the like next to `$ref`
+    #[serde(flatten)]
+    pub keywords: UntypedKeywords,
+
+    /// Checked on strings only if [`ValidationOptions::assert_formats`] is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+
+    /// Bounds of numbers, strings and arrays, each only applies to instances of its type
+    #[serde(flatten)]
+    pub number: NumberCriteria,
+
+    #[serde(flatten)]
+    pub string: StringCriteria,
+
+    #[serde(flatten)]
+    pub array: ArrayCriteria,
+
+    #[serde(flatten)]
+    pub values: ValueCriteria,
+
+    /// Keywords next to `$ref` that this crate does not model
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-#[derive(Debug)]
-enum Data<'a> {
-    Map(&'a HashMap<String, Property>),
-    Prop(&'a Property),
-    Instance(&'a PropertyInstance),
-    Schema(&'a Schema),
+/// A subschema without a single `type`, e.g. `{}` or `{"type": ["string", "null"]}`
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct AnyProperty {
+    #[serde(flatten)]
+    pub annotations: Annotations,
+
+    #[serde(flatten)]
+    pub applicators: Applicators,
+
+    #[serde(flatten)]
+    pub keywords: UntypedKeywords,
+
+    /// Checked on strings only if [`ValidationOptions::assert_formats`] is enabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+
+    /// Bounds of numbers, strings and arrays, each only applies to instances of its type
+    #[serde(flatten)]
+    pub number: NumberCriteria,
+
+    #[serde(flatten)]
+    pub string: StringCriteria,
+
+    #[serde(flatten)]
+    pub array: ArrayCriteria,
+
+    #[serde(flatten)]
+    pub values: ValueCriteria,
+
+    /// Keywords that this crate does not model
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
-fn get_items(p: &Property) -> Option<&PropertyInstance> {
-    match p {
-        Property::Value(PropertyInstance::Array { items }) => Some(&**items),
-        _ => None,
-    }
+/// The keywords of the types, on a subschema without a single `type` or next to a `$ref`.
+///
+/// Each only applies to instances of its type, e.g. `properties` to objects.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UntypedKeywords {
+    /// Several types, or a single one next to a `$ref`
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub types: Option<Types>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub properties: Option<SchemaMap<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub required: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pattern_properties: Option<SchemaMap<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub additional_properties: Option<Box<Property>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub items: Option<Items>,
 }
 
-fn get_properties_instance(p: &PropertyInstance) -> Option<&HashMap<String, Property>> {
-    match p {
-        PropertyInstance::Object { properties, .. } => Some(properties),
-        _ => None,
-    }
+/// The value of `type`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Types {
+    Single(String),
+    /// Any one of these
+    Several(Vec<String>),
 }
 
-fn get_properties(p: &Property) -> Option<&HashMap<String, Property>> {
-    match p {
-        Property::Value(v) => get_properties_instance(v),
-        _ => None,
+/// The value of `items`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Items {
+    /// Applies to every element
+    Each(Box<Property>),
+    /// One subschema for each position, as before 2020-12
+    Positional(Vec<Property>),
+}
+
+static NO_ANNOTATIONS: Annotations = Annotations {
+    title: None,
+    description: None,
+    default: None,
+    examples: None,
+    read_only: None,
+    write_only: None,
+    deprecated: None,
+    comment: None,
+    content_encoding: None,
+    content_media_type: None,
+    content_schema: None,
+};
+
+fn no_extra() -> &'static Map<String, Value> {
+    static NO_EXTRA: OnceLock<Map<String, Value>> = OnceLock::new();
+    NO_EXTRA.get_or_init(Map::new)
+}
+
+impl Property {
+    /// Title, description and other keywords that only describe this subschema
+    pub fn annotations(&self) -> &Annotations {
+        match self {
+            Property::Value(instance) => instance.annotations(),
+            Property::Ref(reference) => &reference.annotations,
+            Property::Any(any) => &any.annotations,
+            Property::Boolean(_) => &NO_ANNOTATIONS,
+        }
+    }
+
+    /// `allOf`, `anyOf`, `if` and the other keywords that may appear next to any `type`
+    pub fn applicators(&self) -> &Applicators {
+        match self {
+            Property::Value(instance) => instance.applicators(),
+            Property::Ref(reference) => &reference.applicators,
+            Property::Any(any) => &any.applicators,
+            Property::Boolean(_) => &NO_APPLICATORS,
+        }
+    }
+
+    /// Like [`Property::annotations`], `true` and `false` become `{}` and `{"not": {}}` to carry them
+    pub fn annotations_mut(&mut self) -> &mut Annotations {
+        self.expand_boolean();
+        self.parts_mut().expect("booleans were expanded").0
+    }
+
+    /// Like [`Property::applicators`], `true` and `false` become `{}` and `{"not": {}}` to carry them
+    pub fn applicators_mut(&mut self) -> &mut Applicators {
+        self.expand_boolean();
+        self.parts_mut().expect("booleans were expanded").1
+    }
+
+    /// Whether this is the `false` schema, which does not accept anything
+    pub fn is_false(&self) -> bool {
+        matches!(self, Property::Boolean(false))
+    }
+
+    pub fn title(&self) -> Option<&str> {
+        self.annotations().title.as_deref()
+    }
+
+    pub fn description(&self) -> Option<&str> {
+        self.annotations().description.as_deref()
+    }
+
+    pub fn default(&self) -> Option<&Value> {
+        self.annotations().default.as_ref()
+    }
+
+    pub fn examples(&self) -> &[Value] {
+        self.annotations().examples.as_deref().unwrap_or_default()
+    }
+
+    pub fn read_only(&self) -> bool {
+        self.annotations().read_only.unwrap_or(false)
+    }
+
+    pub fn write_only(&self) -> bool {
+        self.annotations().write_only.unwrap_or(false)
+    }
+
+    pub fn deprecated(&self) -> bool {
+        self.annotations().deprecated.unwrap_or(false)
     }
+
+    pub fn comment(&self) -> Option<&str> {
+        self.annotations().comment.as_deref()
+    }
+
+    pub fn content_encoding(&self) -> Option<&str> {
+        self.annotations().content_encoding.as_deref()
+    }
+
+    pub fn content_media_type(&self) -> Option<&str> {
+        self.annotations().content_media_type.as_deref()
+    }
+
+    pub fn content_schema(&self) -> Option<&Property> {
+        self.annotations().content_schema.as_deref()
+    }
+
+    /// Keywords that this crate does not model, such as `x-*` extensions
+    pub fn extra(&self) -> &Map<String, Value> {
+        match self {
+            Property::Value(instance) => instance.extra(),
+            Property::Ref(reference) => &reference.extra,
+            Property::Any(any) => &any.extra,
+            Property::Boolean(_) => no_extra(),
+        }
+    }
+
+    /// Looks up a keyword that this crate does not model by its name
+    pub fn extra_keyword(&self, name: &str) -> Option<&Value> {
+        self.extra().get(name)
+    }
+}
+
+/// How [`Schema::dereferenced_with`] treats references that can't be inlined because they are recursive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RecursiveRefs {
+    /// Leave the `$ref` in place
+    Keep,
+    /// Fail with [`ErrorKind::RecursiveRef`]
+    Error,
+}
+
+#[derive(Debug)]
+enum Data<'a> {
+    Prop(&'a Property),
+    Definitions(&'a SchemaMap<SchemaDefinition>),
+    Definition(&'a SchemaDefinition),
+}
+
+fn find_child<'a, 'p>(
+    property: &'a Property,
+    keyword: &str,
+    path: &mut impl Iterator<Item = &'p str>,
+) -> Option<Data<'a>> {
+    let subschemas = property.subschemas();
+    // e.g. `properties` and `allOf` are followed by a name or index
+    let keyed = subschemas
+        .iter()
+        .any(|(k, child_key, _)| *k == keyword && child_key.is_some());
+    let key = if keyed { path.next() } else { None };
+    subschemas
+        .into_iter()
+        .find(|(k, child_key, _)| *k == keyword && child_key.as_deref() == key)
+        .map(|(_, _, child)| Data::Prop(child))
 }
 
-fn find_ref<'a>(mut path: Split<'a, char>, mut data: Data<'a>) -> Option<Data<'a>> {
+fn find_ref<'a, 'p>(
+    mut path: impl Iterator<Item = &'p str>,
+    mut data: Data<'a>,
+) -> Option<Data<'a>> {
     loop {
         let Some(branch) = path.next() else {
             return Some(data);
         };
         data = match (branch, data) {
-            ("properties", Data::Instance(v)) => Data::Map(get_properties_instance(v)?),
-            ("properties", Data::Map(v)) => Data::Map(get_properties(v.get(branch)?)?),
-            ("properties", Data::Prop(v)) => Data::Map(get_properties(v)?),
-            ("properties", Data::Schema(v)) => Data::Map(v.properties()?),
-            ("items", Data::Prop(v)) => Data::Instance(get_items(v)?),
-            (_, Data::Map(v)) => Data::Prop(v.get(branch)?),
-            _ => return None,
+            ("definitions", Data::Definition(v)) => Data::Definitions(v.definitions.as_ref()?),
+            ("$defs", Data::Definition(v)) => Data::Definitions(v.defs.as_ref()?),
+            (_, Data::Definitions(v)) => Data::Definition(v.get(branch)?),
+            (_, Data::Definition(v)) => find_child(v.specification.as_ref()?, branch, &mut path)?,
+            (_, Data::Prop(v)) => find_child(v, branch, &mut path)?,
         };
     }
 }
 
 impl RefProperty {
-    pub fn deref<'a>(&'a self, schema: &'a Schema) -> Option<&PropertyInstance> {
-        let reference = self.reference.strip_prefix("#/")?;
-        let path = reference.split('/');
-        match find_ref(path, Data::Schema(schema))? {
-            Data::Prop(v) => match v {
-                Property::Ref(v) => Some(v.deref(schema)?),
-                Property::Value(v) => Some(v),
-            },
-            Data::Instance(v) => Some(v),
+    /// Locates the document a non-local reference points into, by comparing against the `$id`s of
+    /// the root schema and its embedded resources.
+    fn locate<'a>(&self, schema: &'a Schema) -> Option<(Data<'a>, String)> {
+        let root = schema.as_definition()?;
+        let base = match &root.id {
+            Some(SchemaId::Url(id)) => Some(id),
             _ => None,
+        };
+        let mut url = match base {
+            Some(base) => base.join(&self.reference).ok()?,
+            None => Url::parse(&self.reference).ok()?,
+        };
+        let fragment = url.fragment().unwrap_or_default().to_owned();
+        url.set_fragment(None);
+        if base.is_some_and(|base| base.as_str().trim_end_matches('#') == url.as_str()) {
+            Some((Data::Definition(root), fragment))
+        } else {
+            Some((Data::Definition(root.find_resource(&url)?), fragment))
+        }
+    }
+
+    /// Looks up the subschema this reference points at, without following further references
+    fn lookup<'a>(&self, schema: &'a Schema) -> Result<&'a Property> {
+        let segments = |pointer: &str| -> Vec<String> {
+            pointer
+                .split('/')
+                .filter(|segment| !segment.is_empty())
+                .map(|segment| segment.replace("~1", "/").replace("~0", "~"))
+                .collect()
+        };
+        let target = match self.reference.strip_prefix('#') {
+            Some(pointer) => schema.as_definition().and_then(|root| {
+                find_ref(
+                    segments(pointer).iter().map(String::as_str),
+                    Data::Definition(root),
+                )
+            }),
+            None => self.locate(schema).and_then(|(data, pointer)| {
+                find_ref(segments(&pointer).iter().map(String::as_str), data)
+            }),
+        };
+        let property = match target {
+            Some(Data::Prop(v)) => Some(v),
+            Some(Data::Definition(v)) => v.specification.as_ref(),
+            _ => None,
+        };
+        property.ok_or_else(|| ErrorKind::UnresolvableRef(self.reference.clone()).into())
+    }
+
+    /// Resolves this reference within `schema`, following chained references.
+    ///
+    /// Fails with [`ErrorKind::RefCycle`] if the chain leads back to a reference it has already
+    /// visited, e.g. `{"$ref": "#"}`.
+    pub fn resolve<'a>(&'a self, schema: &'a Schema) -> Result<&'a Property> {
+        let mut visited = vec![self.reference.clone()];
+        let mut property = self.lookup(schema)?;
+        while let Property::Ref(next) = property {
+            let seen = visited.contains(&next.reference);
+            visited.push(next.reference.clone());
+            if seen {
+                return Err(ErrorKind::RefCycle(visited).into());
+            }
+            property = next.lookup(schema)?;
+        }
+        Ok(property)
+    }
+
+    pub fn deref<'a>(&'a self, schema: &'a Schema) -> Option<&'a PropertyInstance> {
+        match self.resolve(schema).ok()? {
+            Property::Value(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Outcome {
+        let outcome = match context.root {
+            Some(root) => match self.resolve(root) {
+                Ok(target) => {
+                    context.follow(&self.reference, |context| target.validate_in(json, context))
+                }
+                Err(e) => Err(vec![context.error(e.to_string())]),
+            },
+            None => Err(vec![context.error(format!(
+                "unable to resolve reference {:?} without a root schema",
+                self.reference
+            ))]),
+        };
+        // keywords next to `$ref` are ignored before 2019-09
+        if context.draft_at_least(Draft::Draft201909) {
+            let mut report = Report::default();
+            report.add(outcome);
+            report.add(self.keywords.validate_in(json, context));
+            report.errors.extend(check_criteria(
+                &self.number,
+                &self.string,
+                self.format.as_deref(),
+                &self.array,
+                &self.values,
+                json,
+                context,
+            ));
+            self.applicators.apply(json, context, report.into_outcome())
+        } else {
+            outcome
+        }
+    }
+}
+
+/// The keywords of a subschema that may reject an instance, in the same shape whether it declares
+/// a single `type`, several, none or sits next to a `$ref`. `true` and `false` have none of them.
+#[derive(Debug, Clone)]
+pub(crate) struct Constraints<'a> {
+    /// `None` if every type is allowed
+    pub types: Option<Vec<&'a str>>,
+    pub number: Option<&'a NumberCriteria>,
+    pub string: Option<&'a StringCriteria>,
+    pub format: Option<&'a str>,
+    pub array: Option<&'a ArrayCriteria>,
+    pub items: Option<ItemsOf<'a>>,
+    pub object: ObjectKeywords<'a>,
+    pub values: Option<&'a ValueCriteria>,
+    pub applicators: &'a Applicators,
+    pub extra: &'a Map<String, Value>,
+}
+
+/// `items` of a subschema, whether it declares `"type": "array"` or not
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ItemsOf<'a> {
+    Each(&'a Property),
+    Positional(&'a [Property]),
+}
+
+impl<'a> Constraints<'a> {
+    /// The keywords of a subschema without a single `type`, its criteria are filled in by the caller
+    fn untyped(
+        keywords: &'a UntypedKeywords,
+        applicators: &'a Applicators,
+        extra: &'a Map<String, Value>,
+    ) -> Self {
+        Constraints {
+            types: keywords
+                .types
+                .as_ref()
+                .map(|types| types.names().iter().map(String::as_str).collect()),
+            number: None,
+            string: None,
+            format: None,
+            array: None,
+            items: match &keywords.items {
+                Some(Items::Each(items)) => Some(ItemsOf::Each(items)),
+                Some(Items::Positional(items)) => Some(ItemsOf::Positional(items)),
+                None => None,
+            },
+            object: keywords.object_keywords(),
+            values: None,
+            applicators,
+            extra,
+        }
+    }
+}
+
+/// `properties`, `required`, `patternProperties` and `additionalProperties` of a subschema,
+/// whether it declares `"type": "object"` or not
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ObjectKeywords<'a> {
+    pub properties: Option<&'a SchemaMap<Property>>,
+    pub required: &'a [String],
+    pub pattern_properties: Option<&'a SchemaMap<Property>>,
+    pub additional_properties: Option<&'a Property>,
+}
+
+impl ObjectKeywords<'_> {
+    const NONE: ObjectKeywords<'static> = ObjectKeywords {
+        properties: None,
+        required: &[],
+        pattern_properties: None,
+        additional_properties: None,
+    };
+
+    fn validate_in(&self, object: &Map<String, Value>, context: &mut Context) -> Outcome {
+        let mut report = Report::default();
+        for name in self.required {
+            if !object.contains_key(name) {
+                report.error(context.error(format!(
+                    "object doesn't contain the required property {:?}",
+                    name
+                )));
+            }
+        }
+
+        for (name, value) in object {
+            if context.done() {
+                break;
+            }
+            let mut subschemas: Vec<&Property> = self
+                .properties
+                .and_then(|properties| properties.get(name))
+                .into_iter()
+                .collect();
+            for (pattern, subschema) in self.pattern_properties.into_iter().flatten() {
+                match context
+                    .regex(pattern)
+                    .and_then(|regex| regex.is_match(name))
+                {
+                    Ok(true) => subschemas.push(subschema),
+                    Ok(false) => {}
+                    Err(error) => report.error(context.error(error)),
+                }
+            }
+            let additional = subschemas.is_empty();
+            match self.additional_properties {
+                Some(subschema) if additional => subschemas.push(subschema),
+                _ if additional => continue,
+                _ => {}
+            }
+
+            for subschema in subschemas {
+                if additional && subschema.is_false() {
+                    report.error(
+                        context.error(format!("additional property {:?} is not allowed", name)),
+                    );
+                } else if let Err(errors) =
+                    context.descend(name, |context| subschema.validate_in(value, context))
+                {
+                    report.errors.extend(errors);
+                }
+            }
+            context.evaluated_property(name);
+            report.evaluated.properties.insert(name.clone());
+        }
+        report.into_outcome()
+    }
+}
+
+/// Validates each element against its subschema, given with its index
+fn validate_elements<'p>(
+    elements: impl Iterator<Item = (usize, &'p Value, &'p Property)>,
+    context: &mut Context,
+) -> Outcome {
+    let mut report = Report::default();
+    for (index, value, subschema) in elements {
+        if context.done() {
+            break;
+        }
+        if let Err(errors) = context.descend(index, |context| subschema.validate_in(value, context))
+        {
+            report.errors.extend(errors);
+        }
+        context.evaluated_item(index);
+        report.evaluated.items.insert(index);
+    }
+    report.into_outcome()
+}
+
+/// The outcome of keywords that don't evaluate any part of the instance
+fn checked(errors: Vec<String>) -> Outcome {
+    if errors.is_empty() {
+        Ok(Evaluated::default())
+    } else {
+        Err(errors)
+    }
+}
+
+/// The bounds, `format`, `enum` and `const` of a subschema without a single `type`
+fn check_criteria(
+    number: &NumberCriteria,
+    string: &StringCriteria,
+    format: Option<&str>,
+    array: &ArrayCriteria,
+    values: &ValueCriteria,
+    json: &Value,
+    context: &mut Context,
+) -> Vec<String> {
+    let mut errors = number.validate_in(json, context);
+    errors.extend(string.validate_in(json, context));
+    errors.extend(check_format(format, json, context));
+    errors.extend(array.validate_in(json, context));
+    errors.extend(values.validate_in(json, context));
+    errors
+}
+
+/// `format` only applies to strings
+fn check_format(format: Option<&str>, json: &Value, context: &mut Context) -> Vec<String> {
+    match (format, json) {
+        (Some(format), Value::String(value)) => match context.options.check_format(format, value) {
+            Ok(()) => Vec::new(),
+            Err(error) => vec![context.error(error)],
+        },
+        _ => Vec::new(),
+    }
+}
+
+/// Whether `json` is an instance of the type `name`
+fn has_type(json: &Value, name: &str) -> bool {
+    match (name, json) {
+        ("null", Value::Null)
+        | ("boolean", Value::Bool(_))
+        | ("number", Value::Number(_))
+        | ("string", Value::String(_))
+        | ("array", Value::Array(_))
+        | ("object", Value::Object(_)) => true,
+        ("integer", Value::Number(number)) => is_integer(number),
+        _ => false,
+    }
+}
+
+/// Whether `number` has no fractional part, like `1` or `1.0`
+fn is_integer(number: &Number) -> bool {
+    number.is_i64() || number.is_u64() || number.as_f64().is_some_and(|n| n.fract() == 0.0)
+}
+
+impl Types {
+    /// The names of the types
+    pub fn names(&self) -> &[String] {
+        match self {
+            Types::Single(name) => std::slice::from_ref(name),
+            Types::Several(names) => names,
+        }
+    }
+}
+
+impl UntypedKeywords {
+    pub(crate) fn object_keywords(&self) -> ObjectKeywords<'_> {
+        ObjectKeywords {
+            properties: self.properties.as_ref(),
+            required: self.required.as_deref().unwrap_or_default(),
+            pattern_properties: self.pattern_properties.as_ref(),
+            additional_properties: self.additional_properties.as_deref(),
+        }
+    }
+
+    fn subschemas(&self) -> Vec<(&'static str, Option<String>, &Property)> {
+        let mut subschemas = Vec::new();
+        for (name, subschema) in self.properties.iter().flatten() {
+            subschemas.push(("properties", Some(name.clone()), subschema));
+        }
+        for (pattern, subschema) in self.pattern_properties.iter().flatten() {
+            subschemas.push(("patternProperties", Some(pattern.clone()), subschema));
+        }
+        if let Some(subschema) = &self.additional_properties {
+            subschemas.push(("additionalProperties", None, &**subschema));
+        }
+        match &self.items {
+            Some(Items::Each(subschema)) => subschemas.push(("items", None, &**subschema)),
+            Some(Items::Positional(list)) => {
+                for (index, subschema) in list.iter().enumerate() {
+                    subschemas.push(("items", Some(index.to_string()), subschema));
+                }
+            }
+            None => {}
         }
+        subschemas
+    }
+
+    fn subschemas_mut(&mut self) -> Vec<(&'static str, Option<String>, &mut Property)> {
+        let mut subschemas = Vec::new();
+        for (name, subschema) in self.properties.iter_mut().flatten() {
+            subschemas.push(("properties", Some(name.clone()), subschema));
+        }
+        for (pattern, subschema) in self.pattern_properties.iter_mut().flatten() {
+            subschemas.push(("patternProperties", Some(pattern.clone()), subschema));
+        }
+        if let Some(subschema) = &mut self.additional_properties {
+            subschemas.push(("additionalProperties", None, &mut **subschema));
+        }
+        match &mut self.items {
+            Some(Items::Each(subschema)) => subschemas.push(("items", None, &mut **subschema)),
+            Some(Items::Positional(list)) => {
+                for (index, subschema) in list.iter_mut().enumerate() {
+                    subschemas.push(("items", Some(index.to_string()), subschema));
+                }
+            }
+            None => {}
+        }
+        subschemas
+    }
+
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Outcome {
+        if let Some(types) = &self.types {
+            if !types.names().iter().any(|name| has_type(json, name)) {
+                return Err(vec![context.error(format!(
+                    "expected one of {:?} found {:?}",
+                    types.names(),
+                    json
+                ))]);
+            }
+        }
+        match (json, &self.items) {
+            (Value::Object(object), _) => self.object_keywords().validate_in(object, context),
+            (Value::Array(elements), Some(Items::Each(items))) => validate_elements(
+                elements
+                    .iter()
+                    .enumerate()
+                    .map(|(index, value)| (index, value, &**items)),
+                context,
+            ),
+            (Value::Array(elements), Some(Items::Positional(list))) => validate_elements(
+                elements
+                    .iter()
+                    .zip(list)
+                    .enumerate()
+                    .map(|(index, (value, subschema))| (index, value, subschema)),
+                context,
+            ),
+            _ => Ok(Evaluated::default()),
+        }
+    }
+}
+
+impl Property {
+    /// The subschemas directly below this one, each with its keyword and,
+    /// for keywords with several subschemas, the property name, pattern or index it is found under
+    pub(crate) fn subschemas(&self) -> Vec<(&'static str, Option<String>, &Property)> {
+        let mut subschemas = Vec::new();
+        match self {
+            Property::Value(PropertyInstance::Object {
+                properties,
+                pattern_properties,
+                additional_properties,
+                ..
+            }) => {
+                for (name, subschema) in properties {
+                    subschemas.push(("properties", Some(name.clone()), subschema));
+                }
+                for (pattern, subschema) in pattern_properties.iter().flatten() {
+                    subschemas.push(("patternProperties", Some(pattern.clone()), subschema));
+                }
+                if let Some(subschema) = additional_properties {
+                    subschemas.push(("additionalProperties", None, &**subschema));
+                }
+            }
+            Property::Value(PropertyInstance::Array { items, .. }) => {
+                subschemas.push(("items", None, &**items));
+            }
+            Property::Ref(RefProperty { keywords, .. })
+            | Property::Any(AnyProperty { keywords, .. }) => {
+                subschemas.extend(keywords.subschemas());
+            }
+            _ => {}
+        }
+        if let Some(subschema) = &self.annotations().content_schema {
+            subschemas.push(("contentSchema", None, &**subschema));
+        }
+        subschemas.extend(self.applicators().subschemas());
+        subschemas
+    }
+
+    pub(crate) fn subschemas_mut(&mut self) -> Vec<(&'static str, Option<String>, &mut Property)> {
+        let mut subschemas = Vec::new();
+        let (annotations, applicators) = match self {
+            Property::Value(PropertyInstance::Object {
+                properties,
+                pattern_properties,
+                additional_properties,
+                annotations,
+                applicators,
+                ..
+            }) => {
+                for (name, subschema) in properties.iter_mut() {
+                    subschemas.push(("properties", Some(name.clone()), subschema));
+                }
+                for (pattern, subschema) in pattern_properties.iter_mut().flatten() {
+                    subschemas.push(("patternProperties", Some(pattern.clone()), subschema));
+                }
+                if let Some(subschema) = additional_properties {
+                    subschemas.push(("additionalProperties", None, &mut **subschema));
+                }
+                (annotations, applicators)
+            }
+            Property::Value(PropertyInstance::Array {
+                items,
+                annotations,
+                applicators,
+                ..
+            }) => {
+                subschemas.push(("items", None, &mut **items));
+                (annotations, applicators)
+            }
+            Property::Ref(RefProperty {
+                keywords,
+                annotations,
+                applicators,
+                ..
+            })
+            | Property::Any(AnyProperty {
+                keywords,
+                annotations,
+                applicators,
+                ..
+            }) => {
+                subschemas.extend(keywords.subschemas_mut());
+                (annotations, applicators)
+            }
+            Property::Value(instance) => {
+                let (annotations, applicators, _) = instance.parts_mut();
+                (annotations, applicators)
+            }
+            Property::Boolean(_) => return subschemas,
+        };
+        if let Some(subschema) = &mut annotations.content_schema {
+            subschemas.push(("contentSchema", None, &mut **subschema));
+        }
+        subschemas.extend(applicators.subschemas_mut());
+        subschemas
+    }
+
+    /// The keywords that may reject an instance, without following a `$ref`
+    pub(crate) fn constraints(&self) -> Constraints<'_> {
+        let instance = match self {
+            Property::Value(instance) => instance,
+            Property::Ref(reference) => {
+                return Constraints {
+                    number: Some(&reference.number),
+                    string: Some(&reference.string),
+                    format: reference.format.as_deref(),
+                    array: Some(&reference.array),
+                    values: Some(&reference.values),
+                    ..Constraints::untyped(
+                        &reference.keywords,
+                        &reference.applicators,
+                        &reference.extra,
+                    )
+                }
+            }
+            Property::Any(any) => {
+                return Constraints {
+                    number: Some(&any.number),
+                    string: Some(&any.string),
+                    format: any.format.as_deref(),
+                    array: Some(&any.array),
+                    values: Some(&any.values),
+                    ..Constraints::untyped(&any.keywords, &any.applicators, &any.extra)
+                }
+            }
+            Property::Boolean(_) => {
+                return Constraints {
+                    types: None,
+                    number: None,
+                    string: None,
+                    format: None,
+                    array: None,
+                    items: None,
+                    object: ObjectKeywords::NONE,
+                    values: None,
+                    applicators: &NO_APPLICATORS,
+                    extra: no_extra(),
+                }
+            }
+        };
+        let mut constraints = Constraints {
+            types: Some(vec![instance.type_name()]),
+            number: None,
+            string: None,
+            format: instance.format(),
+            array: None,
+            items: None,
+            object: ObjectKeywords::NONE,
+            values: Some(instance.values()),
+            applicators: instance.applicators(),
+            extra: instance.extra(),
+        };
+        match instance {
+            PropertyInstance::Integer { criteria, .. }
+            | PropertyInstance::Number { criteria, .. } => constraints.number = Some(criteria),
+            PropertyInstance::String { criteria, .. } => constraints.string = Some(criteria),
+            PropertyInstance::Array {
+                items, criteria, ..
+            } => {
+                constraints.array = Some(criteria);
+                constraints.items = Some(ItemsOf::Each(items));
+            }
+            PropertyInstance::Object {
+                properties,
+                required,
+                pattern_properties,
+                additional_properties,
+                ..
+            } => {
+                constraints.object = ObjectKeywords {
+                    properties: Some(properties),
+                    required: required.as_deref().unwrap_or_default(),
+                    pattern_properties: pattern_properties.as_ref(),
+                    additional_properties: additional_properties.as_deref(),
+                }
+            }
+            _ => {}
+        }
+        constraints
+    }
+
+    /// Turns `true` and `false` into the equivalent `{}` and `{"not": {}}`, which can carry keywords
+    pub(crate) fn expand_boolean(&mut self) {
+        if let Property::Boolean(value) = *self {
+            let mut any = AnyProperty::default();
+            if !value {
+                any.applicators.not = Some(Box::new(Property::Any(AnyProperty::default())));
+            }
+            *self = Property::Any(any);
+        }
+    }
+
+    /// The keywords every subschema may carry, `None` for `true` and `false`
+    pub(crate) fn parts_mut(
+        &mut self,
+    ) -> Option<(&mut Annotations, &mut Applicators, &mut Map<String, Value>)> {
+        match self {
+            Property::Value(instance) => Some(instance.parts_mut()),
+            Property::Ref(reference) => Some((
+                &mut reference.annotations,
+                &mut reference.applicators,
+                &mut reference.extra,
+            )),
+            Property::Any(any) => {
+                Some((&mut any.annotations, &mut any.applicators, &mut any.extra))
+            }
+            Property::Boolean(_) => None,
+        }
+    }
+
+    pub(crate) fn try_for_each_ref_mut<E>(
+        &mut self,
+        f: &mut impl FnMut(&mut RefProperty) -> std::result::Result<(), E>,
+    ) -> std::result::Result<(), E> {
+        if let Property::Ref(reference) = self {
+            f(reference)?;
+        }
+        self.subschemas_mut()
+            .into_iter()
+            .try_for_each(|(_, _, subschema)| subschema.try_for_each_ref_mut(f))
+    }
+
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Outcome {
+        context.annotate(self.annotations());
+        let outcome = match self {
+            Property::Value(instance) => instance.validate_in(json, context),
+            Property::Ref(reference) => reference.validate_in(json, context),
+            Property::Any(any) => {
+                let mut report = Report::default();
+                report.add(any.keywords.validate_in(json, context));
+                report.errors.extend(check_criteria(
+                    &any.number,
+                    &any.string,
+                    any.format.as_deref(),
+                    &any.array,
+                    &any.values,
+                    json,
+                    context,
+                ));
+                let outcome = report.into_outcome();
+                any.applicators.apply(json, context, outcome)
+            }
+            Property::Boolean(true) => Ok(Evaluated::default()),
+            Property::Boolean(false) => {
+                Err(vec![context.error(format!(
+                    r#"the schema "false" does not accept {:?}"#,
+                    json
+                ))])
+            }
+        };
+        let mut report = Report::default();
+        report.add(outcome);
+        report
+            .errors
+            .extend(context.apply_keywords(self.extra(), json));
+        report
+            .errors
+            .extend(context.check_content(self.annotations(), json));
+        report.into_outcome()
     }
 }
 
@@ -85,121 +992,376 @@ impl RefProperty {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum PropertyInstance {
-    Null,
+    Null {
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
 
-    Boolean,
+    Boolean {
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
 
     Integer {
         #[serde(flatten)]
         criteria: NumberCriteria,
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
     Object {
-        properties: HashMap<String, Property>,
+        #[serde(default)]
+        properties: SchemaMap<Property>,
+        #[serde(skip_serializing_if = "Option::is_none")]
         required: Option<Vec<String>>,
+        #[serde(rename = "patternProperties", skip_serializing_if = "Option::is_none")]
+        pattern_properties: Option<SchemaMap<Property>>,
+        #[serde(
+            rename = "additionalProperties",
+            skip_serializing_if = "Option::is_none"
+        )]
+        additional_properties: Option<Box<Property>>,
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
 
     Array {
-        items: Box<PropertyInstance>,
+        items: Box<Property>,
+        #[serde(flatten)]
+        criteria: ArrayCriteria,
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
 
     Number {
         #[serde(flatten)]
         criteria: NumberCriteria,
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
     },
 
-    String,
+    String {
+        /// Checked only if [`ValidationOptions::assert_formats`] is enabled
+        #[serde(skip_serializing_if = "Option::is_none")]
+        format: Option<String>,
+        #[serde(flatten)]
+        criteria: StringCriteria,
+        #[serde(flatten)]
+        values: ValueCriteria,
+        #[serde(flatten)]
+        annotations: Annotations,
+        #[serde(flatten)]
+        applicators: Applicators,
+        #[serde(flatten)]
+        extra: Map<String, Value>,
+    },
 }
 
 impl PropertyInstance {
+    /// Title, description and other keywords that only describe this subschema
+    pub fn annotations(&self) -> &Annotations {
+        use PropertyInstance::*;
+        match self {
+            Null { annotations, .. }
+            | Boolean { annotations, .. }
+            | Integer { annotations, .. }
+            | Object { annotations, .. }
+            | Array { annotations, .. }
+            | Number { annotations, .. }
+            | String { annotations, .. } => annotations,
+        }
+    }
+
+    /// `allOf`, `anyOf`, `if` and the other keywords that may appear next to any `type`
+    pub fn applicators(&self) -> &Applicators {
+        use PropertyInstance::*;
+        match self {
+            Null { applicators, .. }
+            | Boolean { applicators, .. }
+            | Integer { applicators, .. }
+            | Object { applicators, .. }
+            | Array { applicators, .. }
+            | Number { applicators, .. }
+            | String { applicators, .. } => applicators,
+        }
+    }
+
+    pub(crate) fn parts_mut(
+        &mut self,
+    ) -> (&mut Annotations, &mut Applicators, &mut Map<String, Value>) {
+        use PropertyInstance::*;
+        match self {
+            Null {
+                annotations,
+                applicators,
+                extra,
+                ..
+            }
+            | Boolean {
+                annotations,
+                applicators,
+                extra,
+                ..
+            }
+            | Integer {
+                annotations,
+                applicators,
+                extra,
+                ..
+            }
+            | Object {
+                annotations,
+                applicators,
+                extra,
+                ..
+            }
+            | Array {
+                annotations,
+                applicators,
+                extra,
+                ..
+            }
+            | Number {
+                annotations,
+                applicators,
+                extra,
+                ..
+            }
+            | String {
+                annotations,
+                applicators,
+                extra,
+                ..
+            } => (annotations, applicators, extra),
+        }
+    }
+
+    /// The name of the `type`, e.g. `"integer"`
+    pub(crate) fn type_name(&self) -> &'static str {
+        use PropertyInstance::*;
+        match self {
+            Null { .. } => "null",
+            Boolean { .. } => "boolean",
+            Integer { .. } => "integer",
+            Object { .. } => "object",
+            Array { .. } => "array",
+            Number { .. } => "number",
+            String { .. } => "string",
+        }
+    }
+
+    /// `enum` and `const`
+    pub(crate) fn values(&self) -> &ValueCriteria {
+        use PropertyInstance::*;
+        match self {
+            Null { values, .. }
+            | Boolean { values, .. }
+            | Integer { values, .. }
+            | Object { values, .. }
+            | Array { values, .. }
+            | Number { values, .. }
+            | String { values, .. } => values,
+        }
+    }
+
+    pub(crate) fn values_mut(&mut self) -> &mut ValueCriteria {
+        use PropertyInstance::*;
+        match self {
+            Null { values, .. }
+            | Boolean { values, .. }
+            | Integer { values, .. }
+            | Object { values, .. }
+            | Array { values, .. }
+            | Number { values, .. }
+            | String { values, .. } => values,
+        }
+    }
+
+    /// The `format` of a string, e.g. `"date-time"` or `"email"`
+    pub fn format(&self) -> Option<&str> {
+        match self {
+            PropertyInstance::String { format, .. } => format.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// Keywords that this crate does not model, such as `x-*` extensions
+    pub fn extra(&self) -> &Map<String, Value> {
+        use PropertyInstance::*;
+        match self {
+            Null { extra, .. }
+            | Boolean { extra, .. }
+            | Integer { extra, .. }
+            | Object { extra, .. }
+            | Array { extra, .. }
+            | Number { extra, .. }
+            | String { extra, .. } => extra,
+        }
+    }
+
+    /// Looks up a keyword that this crate does not model by its name
+    pub fn extra_keyword(&self, name: &str) -> Option<&Value> {
+        self.extra().get(name)
+    }
+
     /// TODO: implement [validation](https://json-schema.org/latest/json-schema-validation.html)
-    pub fn validate(&self, json: &serde_json::Value) -> Result<(), Vec<String>> {
-        use serde_json::Value;
+    ///
+    /// `$ref`s can't be resolved without the surrounding document, validate through
+    /// [`Schema::validate`] if this instance contains any.
+    pub fn validate(&self, json: &Value) -> std::result::Result<(), Vec<String>> {
+        let options = ValidationOptions::default();
+        self.validate_in(json, &mut Context::new(None, &options))
+            .map(drop)
+    }
+
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Outcome {
+        let mut report = Report::default();
+        report.add(self.validate_type(json, context));
+        report
+            .errors
+            .extend(self.values().validate_in(json, context));
+        self.applicators()
+            .apply(json, context, report.into_outcome())
+    }
+
+    /// Validates the `type` and the keywords that belong to it
+    fn validate_type(&self, json: &Value, context: &mut Context) -> Outcome {
         use PropertyInstance::*;
 
         match (&self, json) {
-            (Null, Value::Null) => Ok(()),
-            (Null, unexpected_value) => {
-                Err(vec![format!("expected null found {:?}", unexpected_value)])
-            }
-
-            (Boolean, Value::Bool(_)) => Ok(()),
-            (Boolean, unexpected_value) => Err(vec![format!(
-                "expected boolean found {:?}",
-                unexpected_value
-            )]),
-
-            (String, Value::String(_)) => Ok(()),
-            (String, unexpected_value) => Err(vec![format!(
-                "expected string found {:?}",
-                unexpected_value
-            )]),
-
-            (Number { .. }, Value::Number(_)) => Ok(()),
-            (Number { .. }, unexpected_value) => Err(vec![format!(
-                "expected number found {:?}",
-                unexpected_value
-            )]),
-
-            (Integer { .. }, Value::Number(i)) if i.is_i64() => Ok(()),
-            (Integer { .. }, unexpected_value) => Err(vec![format!(
-                "expected integer found {:?}",
-                unexpected_value
-            )]),
-
-            (Array { items }, Value::Array(elems)) => {
-                let errors: Vec<std::string::String> = elems
-                    .iter()
-                    .map(|value| items.validate(value))
-                    .filter_map(Result::err)
-                    .flat_map(|errors| errors.into_iter())
-                    .collect();
-                if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(errors)
-                }
+            (Null { .. }, Value::Null) => Ok(Evaluated::default()),
+            (Null { .. }, unexpected_value) => {
+                Err(vec![context.error(format!(
+                    "expected null found {:?}",
+                    unexpected_value
+                ))])
+            }
+
+            (Boolean { .. }, Value::Bool(_)) => Ok(Evaluated::default()),
+            (Boolean { .. }, unexpected_value) => {
+                Err(vec![context.error(format!(
+                    "expected boolean found {:?}",
+                    unexpected_value
+                ))])
+            }
+
+            (
+                String {
+                    format, criteria, ..
+                },
+                Value::String(_),
+            ) => {
+                let mut report = Report::default();
+                report
+                    .errors
+                    .extend(check_format(format.as_deref(), json, context));
+                report.errors.extend(criteria.validate_in(json, context));
+                report.into_outcome()
+            }
+            (String { .. }, unexpected_value) => {
+                Err(vec![context.error(format!(
+                    "expected string found {:?}",
+                    unexpected_value
+                ))])
+            }
+
+            (Number { criteria, .. }, Value::Number(_)) => {
+                checked(criteria.validate_in(json, context))
+            }
+            (Number { .. }, unexpected_value) => {
+                Err(vec![context.error(format!(
+                    "expected number found {:?}",
+                    unexpected_value
+                ))])
+            }
+
+            (Integer { criteria, .. }, Value::Number(i)) if is_integer(i) => {
+                checked(criteria.validate_in(json, context))
+            }
+            (Integer { .. }, unexpected_value) => {
+                Err(vec![context.error(format!(
+                    "expected integer found {:?}",
+                    unexpected_value
+                ))])
+            }
+
+            (
+                Array {
+                    items, criteria, ..
+                },
+                Value::Array(elements),
+            ) => {
+                let mut report = Report::default();
+                report.errors.extend(criteria.validate_in(json, context));
+                report.add(validate_elements(
+                    elements
+                        .iter()
+                        .enumerate()
+                        .map(|(index, value)| (index, value, &**items)),
+                    context,
+                ));
+                report.into_outcome()
             }
             (Array { .. }, unexpected_value) => {
-                Err(vec![format!("expected array found {:?}", unexpected_value)])
+                Err(vec![context.error(format!(
+                    "expected array found {:?}",
+                    unexpected_value
+                ))])
             }
 
             (
                 Object {
                     properties,
                     required,
+                    pattern_properties,
+                    additional_properties,
+                    ..
                 },
                 Value::Object(object),
-            ) => {
-                let errors: Vec<std::string::String> = properties
-                    .iter()
-                    .filter_map(|(k, schema)| {
-                        object
-                            .get(k)
-                            .map(|v| match schema {
-                                Property::Value(schema) => schema.validate(v).err(),
-                                Property::Ref(_schema) => unimplemented!(),
-                            })
-                            .unwrap_or_else(|| {
-                                if required.iter().flat_map(|v| v.iter()).any(|x| x == k) {
-                                    Some(vec![format!(
-                                        "object doesn't contain the required property {:?}",
-                                        k
-                                    )])
-                                } else {
-                                    None
-                                }
-                            })
-                    })
-                    .flat_map(|errors| errors.into_iter())
-                    .collect();
-                if errors.is_empty() {
-                    Ok(())
-                } else {
-                    Err(errors)
-                }
+            ) => ObjectKeywords {
+                properties: Some(properties),
+                required: required.as_deref().unwrap_or_default(),
+                pattern_properties: pattern_properties.as_ref(),
+                additional_properties: additional_properties.as_deref(),
             }
+            .validate_in(object, context),
 
-            (Object { .. }, _) => Err(vec![format!("invalid object")]),
+            (Object { .. }, _) => Err(vec![context.error("invalid object".to_owned())]),
         }
     }
 }
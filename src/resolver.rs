@@ -0,0 +1,34 @@
+//! Retrieving schemas that are referenced from other documents
+
+use url::Url;
+
+use std::collections::HashMap;
+
+use crate::error::{ErrorKind, Result};
+use crate::Schema;
+
+/// Provides the documents behind external `$ref`s
+///
+/// This crate does not do any IO by itself, implement this to load schemas from disk or the network.
+/// It is already implemented for maps of preloaded schemas and for closures.
+pub trait Resolver {
+    /// Retrieves the schema document located at `url`, which never has a fragment.
+    fn resolve(&self, url: &Url) -> Result<Schema>;
+}
+
+impl Resolver for HashMap<Url, Schema> {
+    fn resolve(&self, url: &Url) -> Result<Schema> {
+        self.get(url)
+            .cloned()
+            .ok_or_else(|| ErrorKind::UnresolvableRef(url.to_string()).into())
+    }
+}
+
+impl<F> Resolver for F
+where
+    F: Fn(&Url) -> Result<Schema>,
+{
+    fn resolve(&self, url: &Url) -> Result<Schema> {
+        self(url)
+    }
+}
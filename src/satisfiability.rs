@@ -0,0 +1,163 @@
+//! Finding subschemas that no instance satisfies
+//!
+//! ```
+//! use serde_json::json;
+//! use serde_json_schema::satisfiability::Reason;
+//! use serde_json_schema::{Schema, TryFrom};
+//!
+//! let schema = Schema::try_from(json!({
+//!     "type": "object",
+//!     "properties": { "age": { "type": "integer", "minimum": 18, "maximum": 12 } }
+//! }))
+//! .unwrap();
+//!
+//! let unsatisfiable = schema.unsatisfiable();
+//! assert_eq!(unsatisfiable[0].pointer, "/properties/age");
+//! assert_eq!(
+//!     unsatisfiable[0].reason,
+//!     Reason::EmptyRange { minimum: "minimum", maximum: "maximum" }
+//! );
+//! ```
+
+use crate::draft::Draft;
+use crate::property::{Constraints, ObjectKeywords, Property};
+use crate::subset::{allows_type, intersect_types, is_empty, range, type_of, types, values};
+use crate::visit::Location;
+use crate::Schema;
+
+/// Why no instance satisfies a subschema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// A lower bound like `minimum` or `minLength` is above the upper one, named by the keywords
+    /// that set them, e.g. `exclusiveMinimum`
+    EmptyRange {
+        minimum: &'static str,
+        maximum: &'static str,
+    },
+    /// A `required` property that `properties`, `patternProperties` or `additionalProperties`
+    /// forbid
+    ForbiddenProperty(String),
+    /// `enum` or `const` have no value of an allowed `type`
+    EmptyEnum,
+    /// The `type` has nothing in common with that of an `allOf` subschema
+    EmptyTypes,
+}
+
+/// A subschema that no instance satisfies
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsatisfiable {
+    /// JSON Pointer of the subschema, e.g. `/properties/age`
+    pub pointer: String,
+    pub reason: Reason,
+}
+
+/// A `required` property of an object that can't be there
+fn forbidden<'a>(object: &ObjectKeywords<'a>) -> Option<&'a str> {
+    object
+        .required
+        .iter()
+        .find(|name| {
+            let mut subschemas: Vec<&Property> = object
+                .properties
+                .and_then(|properties| properties.get(*name))
+                .into_iter()
+                .collect();
+            for (pattern, subschema) in object.pattern_properties.into_iter().flatten() {
+                match regex::Regex::new(pattern) {
+                    Ok(pattern) if !pattern.is_match(name) => {}
+                    // an invalid pattern might match
+                    _ => subschemas.push(subschema),
+                }
+            }
+            if subschemas.is_empty() {
+                subschemas.extend(object.additional_properties);
+            }
+            subschemas.iter().any(|subschema| subschema.is_false())
+        })
+        .map(String::as_str)
+}
+
+fn check(root: &Schema, property: &Property) -> Option<Reason> {
+    let siblings_apply = root.draft().is_none_or(|draft| draft >= Draft::Draft201909);
+    if let (Property::Ref(_), false) | (Property::Boolean(_), _) = (property, siblings_apply) {
+        return None;
+    }
+    let constraints = property.constraints();
+
+    let mut names = types(&constraints);
+    for branch in constraints.applicators.all_of.iter().flatten() {
+        let branch = match branch {
+            Property::Ref(reference) => match reference.resolve(root) {
+                Ok(target) => target,
+                Err(_) => continue,
+            },
+            branch => branch,
+        };
+        if let Some(other) = &branch.constraints().types {
+            names = intersect_types(&names, other);
+        }
+    }
+    reason(&constraints, names)
+}
+
+/// Why no instance of the types `names` satisfies `constraints`
+pub(crate) fn reason(constraints: &Constraints, names: Vec<&str>) -> Option<Reason> {
+    if names.is_empty() {
+        return Some(Reason::EmptyTypes);
+    }
+
+    if let Some(values) = values(constraints) {
+        if !values
+            .iter()
+            .any(|value| allows_type(&names, type_of(value)))
+        {
+            return Some(Reason::EmptyEnum);
+        }
+        return None;
+    }
+
+    // unsatisfiable if there is no instance of any of the types
+    let mut reason = None;
+    for name in names {
+        let empty = match range(constraints, name) {
+            Ok(range @ (Some(lower), Some(upper))) if is_empty(range) => Some(Reason::EmptyRange {
+                minimum: lower.keyword,
+                maximum: upper.keyword,
+            }),
+            _ => None,
+        };
+        let empty = empty.or_else(|| match name {
+            "object" => forbidden(&constraints.object)
+                .map(|name| Reason::ForbiddenProperty(name.to_owned())),
+            _ => None,
+        });
+        match empty {
+            Some(empty) => {
+                reason.get_or_insert(empty);
+            }
+            None => return None,
+        }
+    }
+    reason
+}
+
+impl Schema {
+    /// Lists the subschemas that no instance can satisfy, parents first.
+    ///
+    /// These are found by looking at each subschema on its own: `minimum` above `maximum` or
+    /// another empty range, a `required` property that is forbidden, an `enum` without a value of
+    /// the allowed `type`s or `type`s that an `allOf` narrows down to none.
+    /// `false` is taken to be meant that way and is not listed.
+    pub fn unsatisfiable(&self) -> Vec<Unsatisfiable> {
+        let mut unsatisfiable = Vec::new();
+        self.walk(&mut |property: &Property, location: &Location| {
+            if let Some(reason) = check(self, property) {
+                unsatisfiable.push(Unsatisfiable {
+                    pointer: location.pointer.clone(),
+                    reason,
+                });
+            }
+        });
+        unsatisfiable
+    }
+}
@@ -0,0 +1,826 @@
+//! Deciding whether everything one schema accepts is accepted by another
+
+use serde_json::Value;
+
+use crate::applicator::Applicators;
+use crate::draft::Draft;
+use crate::property::{Constraints, ItemsOf, Property};
+use crate::validation::{equal, Context, NumberCriteria, ValidationOptions};
+use crate::Schema;
+
+/// The answer of [`Schema::is_subset_of`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compatibility {
+    /// Every instance the one schema accepts is accepted by the other
+    Compatible,
+    /// Some instance the one schema accepts is rejected by the other
+    Incompatible,
+    /// This crate can't tell, e.g. because of a `pattern`, `oneOf` or a keyword it does not know
+    Unknown,
+}
+
+impl Compatibility {
+    fn and(self, other: Compatibility) -> Compatibility {
+        match (self, other) {
+            (Compatibility::Incompatible, _) | (_, Compatibility::Incompatible) => {
+                Compatibility::Incompatible
+            }
+            (Compatibility::Unknown, _) | (_, Compatibility::Unknown) => Compatibility::Unknown,
+            _ => Compatibility::Compatible,
+        }
+    }
+
+    fn all(answers: impl IntoIterator<Item = Compatibility>) -> Compatibility {
+        answers
+            .into_iter()
+            .fold(Compatibility::Compatible, Compatibility::and)
+    }
+
+    fn from_bool(compatible: bool) -> Compatibility {
+        match compatible {
+            true => Compatibility::Compatible,
+            false => Compatibility::Incompatible,
+        }
+    }
+}
+
+/// Keywords this crate does not model whose instances are understood well enough to find
+/// counterexamples
+const COUNTED: &[&str] = &["minProperties", "maxProperties"];
+
+pub(crate) const TYPES: &[&str] = &[
+    "null", "boolean", "integer", "number", "string", "array", "object",
+];
+
+/// Validation keywords that accept less the greater they are
+pub(crate) const LOWER_BOUNDS: &[&str] = &[
+    "minimum",
+    "exclusiveMinimum",
+    "minLength",
+    "minItems",
+    "minProperties",
+    "minContains",
+];
+
+/// Validation keywords that accept less the smaller they are
+pub(crate) const UPPER_BOUNDS: &[&str] = &[
+    "maximum",
+    "exclusiveMaximum",
+    "maxLength",
+    "maxItems",
+    "maxProperties",
+    "maxContains",
+];
+
+/// Gives up on deeper or recursive schemas
+const MAX_DEPTH: usize = 32;
+
+static ANYTHING: Property = Property::Boolean(true);
+
+/// A minimum or maximum
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Bound {
+    pub value: f64,
+    pub exclusive: bool,
+    /// The keyword that sets it, e.g. `exclusiveMinimum`
+    pub keyword: &'static str,
+}
+
+/// The lower and upper bound of an instance
+pub(crate) type Range = (Option<Bound>, Option<Bound>);
+
+/// The tighter of an inclusive and an exclusive bound on each side, rounded to the closest
+/// integers within them for `integer`
+pub(crate) fn range_of(
+    lower: [Option<Bound>; 2],
+    upper: [Option<Bound>; 2],
+    integer: bool,
+) -> Range {
+    let tighter = |[a, b]: [Option<Bound>; 2], lower: bool| match (a, b) {
+        (Some(a), Some(b)) if a.value == b.value => Some(if a.exclusive { a } else { b }),
+        (Some(a), Some(b)) if (a.value > b.value) == lower => Some(a),
+        (a, b) => b.or(a),
+    };
+    let mut lower = tighter(lower, true);
+    let mut upper = tighter(upper, false);
+    if integer {
+        lower = lower.map(|bound| Bound {
+            value: match bound.exclusive {
+                true => bound.value.floor() + 1.0,
+                false => bound.value.ceil(),
+            },
+            exclusive: false,
+            ..bound
+        });
+        upper = upper.map(|bound| Bound {
+            value: match bound.exclusive {
+                true => bound.value.ceil() - 1.0,
+                false => bound.value.floor(),
+            },
+            exclusive: false,
+            ..bound
+        });
+    }
+    (lower, upper)
+}
+
+pub(crate) fn is_empty((lower, upper): Range) -> bool {
+    match (lower, upper) {
+        (Some(lower), Some(upper)) => {
+            lower.value > upper.value
+                || (lower.value == upper.value && (lower.exclusive || upper.exclusive))
+        }
+        _ => false,
+    }
+}
+
+/// Whether `outer` allows everything `inner` allows
+fn covers((outer_lower, outer_upper): Range, (inner_lower, inner_upper): Range) -> bool {
+    let lower = match (outer_lower, inner_lower) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(outer), Some(inner)) => {
+            inner.value > outer.value
+                || (inner.value == outer.value && (inner.exclusive || !outer.exclusive))
+        }
+    };
+    let upper = match (outer_upper, inner_upper) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(outer), Some(inner)) => {
+            inner.value < outer.value
+                || (inner.value == outer.value && (inner.exclusive || !outer.exclusive))
+        }
+    };
+    lower && upper
+}
+
+pub(crate) fn allows_type(types: &[&str], name: &str) -> bool {
+    types.contains(&name) || (name == "integer" && types.contains(&"number"))
+}
+
+/// The types both allow, e.g. `integer` for `number` and `integer`
+pub(crate) fn intersect_types<'t>(left: &[&'t str], right: &[&'t str]) -> Vec<&'t str> {
+    let mut both: Vec<&str> = left
+        .iter()
+        .filter_map(|name| match *name {
+            "number" if right.contains(&"integer") && !right.contains(&"number") => Some("integer"),
+            name if allows_type(right, name) => Some(name),
+            _ => None,
+        })
+        .collect();
+    // e.g. `integer` once for both `integer` and `number`
+    both.sort_unstable();
+    both.dedup();
+    both
+}
+
+pub(crate) fn type_of(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.as_f64().is_none_or(|n| n.fract() == 0.0) => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// The types a subschema allows
+pub(crate) fn types<'a>(constraints: &Constraints<'a>) -> Vec<&'a str> {
+    constraints.types.clone().unwrap_or_else(|| TYPES.to_vec())
+}
+
+/// A bound given as a number by `keyword`, `Err` for anything else
+fn bound(
+    value: Option<&Value>,
+    exclusive: bool,
+    keyword: &'static str,
+) -> Result<Option<Bound>, ()> {
+    match value {
+        None => Ok(None),
+        Some(value) => value
+            .as_f64()
+            .map(|value| {
+                Some(Bound {
+                    value,
+                    exclusive,
+                    keyword,
+                })
+            })
+            .ok_or(()),
+    }
+}
+
+/// `minimum` and `exclusiveMinimum`, `maximum` and `exclusiveMaximum`
+fn number_bounds(criteria: &NumberCriteria) -> Result<[[Option<Bound>; 2]; 2], ()> {
+    // `true` makes `minimum` or `maximum` exclusive in draft 4
+    let draft4 = |exclusive: &Option<Value>| matches!(exclusive, Some(Value::Bool(true)));
+    let exclusive = |exclusive: &Option<Value>, keyword| match exclusive {
+        Some(Value::Bool(_)) => Ok(None),
+        exclusive => bound(exclusive.as_ref(), true, keyword),
+    };
+    Ok([
+        [
+            bound(
+                criteria.minimum.as_ref(),
+                draft4(&criteria.exclusive_minimum),
+                "minimum",
+            )?,
+            exclusive(&criteria.exclusive_minimum, "exclusiveMinimum")?,
+        ],
+        [
+            bound(
+                criteria.maximum.as_ref(),
+                draft4(&criteria.exclusive_maximum),
+                "maximum",
+            )?,
+            exclusive(&criteria.exclusive_maximum, "exclusiveMaximum")?,
+        ],
+    ])
+}
+
+/// The bounds of the instances of type `name`, e.g. of their length for strings,
+/// `Err` if a bound is no number
+pub(crate) fn range(constraints: &Constraints, name: &str) -> Result<Range, ()> {
+    let counts = |[min, max]: [(Option<&Value>, &'static str); 2]| -> Result<_, ()> {
+        Ok([
+            [bound(min.0, false, min.1)?, None],
+            [bound(max.0, false, max.1)?, None],
+        ])
+    };
+    let [lower, upper] = match (name, constraints) {
+        (
+            "integer" | "number",
+            Constraints {
+                number: Some(criteria),
+                ..
+            },
+        ) => number_bounds(criteria)?,
+        (
+            "string",
+            Constraints {
+                string: Some(criteria),
+                ..
+            },
+        ) => counts([
+            (criteria.min_length.as_ref(), "minLength"),
+            (criteria.max_length.as_ref(), "maxLength"),
+        ])?,
+        (
+            "array",
+            Constraints {
+                array: Some(criteria),
+                ..
+            },
+        ) => counts([
+            (criteria.min_items.as_ref(), "minItems"),
+            (criteria.max_items.as_ref(), "maxItems"),
+        ])?,
+        ("object", _) => counts([
+            (constraints.extra.get("minProperties"), "minProperties"),
+            (constraints.extra.get("maxProperties"), "maxProperties"),
+        ])?,
+        _ => return Ok((None, None)),
+    };
+    Ok(range_of(lower, upper, name != "number"))
+}
+
+/// The values allowed by `enum` and `const`, if there are any
+pub(crate) fn values<'a>(constraints: &Constraints<'a>) -> Option<Vec<&'a Value>> {
+    let criteria = constraints.values?;
+    let mut values: Option<Vec<&Value>> = criteria
+        .enum_values
+        .as_ref()
+        .map(|values| values.iter().collect());
+    if let Some(constant) = &criteria.const_value {
+        values = Some(match values {
+            Some(values) => values
+                .into_iter()
+                .filter(|value| equal(value, constant))
+                .collect(),
+            None => vec![constant],
+        });
+    }
+    values
+}
+
+/// Whether a subschema has no keywords that may reject an instance, like `{}`
+pub(crate) fn is_unconstrained(constraints: &Constraints) -> bool {
+    constraints.types.is_none()
+        && constraints
+            .number
+            .is_none_or(|c| *c == NumberCriteria::default())
+        && constraints.string.is_none_or(|c| *c == Default::default())
+        && constraints.format.is_none()
+        && constraints.array.is_none_or(|c| *c == Default::default())
+        && constraints.items.is_none()
+        && constraints.object.properties.is_none_or(|p| p.is_empty())
+        && constraints.object.required.is_empty()
+        && constraints.object.pattern_properties.is_none()
+        && constraints.object.additional_properties.is_none()
+        && constraints.values.is_none_or(|c| *c == Default::default())
+        && *constraints.applicators == Applicators::default()
+        && constraints.extra.is_empty()
+}
+
+/// Whether `constraints` could allow an instance of type `name`, `Err` if that's unclear
+pub(crate) fn inhabited(constraints: &Constraints, name: &str) -> Result<bool, ()> {
+    if is_empty(range(constraints, name)?) {
+        return Ok(false);
+    }
+    if name == "object" {
+        let object = &constraints.object;
+        let closed = object.additional_properties.is_some_and(Property::is_false);
+        for name in object.required {
+            match object
+                .properties
+                .and_then(|properties| properties.get(name))
+            {
+                Some(Property::Boolean(false)) => return Ok(false),
+                None if closed && object.pattern_properties.is_none() => return Ok(false),
+                _ => {}
+            }
+        }
+    }
+    Ok(true)
+}
+
+fn has_ref(property: &Property) -> bool {
+    matches!(property, Property::Ref(_))
+        || property
+            .subschemas()
+            .into_iter()
+            .any(|(_, _, subschema)| has_ref(subschema))
+}
+
+/// Whether two subschemas are equal without `$ref`s, which may resolve differently in either document
+fn same(left: &Property, right: &Property) -> bool {
+    left == right && !has_ref(left)
+}
+
+/// Both subschemas in one, `None` unless they have different keywords
+fn merge(rest: &Property, branch: &Property) -> Option<Property> {
+    match (rest, branch) {
+        (_, Property::Boolean(false)) | (Property::Boolean(false), _) => {
+            return Some(Property::Boolean(false))
+        }
+        (_, branch) if is_unconstrained(&branch.constraints()) => return Some(rest.clone()),
+        (rest, _) if is_unconstrained(&rest.constraints()) => return Some(branch.clone()),
+        _ => {}
+    }
+    // the keywords are joined as they appear in the document, without the annotations of `branch`
+    let mut branch = branch.clone();
+    *branch.annotations_mut() = Default::default();
+    let (mut keywords, other) = match (serde_json::to_value(rest), serde_json::to_value(branch)) {
+        (Ok(Value::Object(keywords)), Ok(Value::Object(other))) => (keywords, other),
+        _ => return None,
+    };
+    for (keyword, value) in other {
+        if keywords.contains_key(&keyword)
+            || ["allOf", "anyOf", "oneOf", "$ref"].contains(&keyword.as_str())
+        {
+            return None;
+        }
+        keywords.insert(keyword, value);
+    }
+    serde_json::from_value(Value::Object(keywords)).ok()
+}
+
+/// Whether a subschema has keywords that validation does not check but which may reject an
+/// instance, like `format` or unknown ones
+fn undecided(property: &Property) -> bool {
+    let constraints = property.constraints();
+    !constraints.extra.is_empty()
+        || constraints.format.is_some()
+        || property
+            .subschemas()
+            .into_iter()
+            .any(|(_, _, subschema)| undecided(subschema))
+}
+
+struct Subset<'s> {
+    left: &'s Schema,
+    right: &'s Schema,
+    depth: usize,
+}
+
+/// Which document a subschema belongs to, for resolving its `$ref`s
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+}
+
+impl<'s> Subset<'s> {
+    fn schema(&self, side: Side) -> &'s Schema {
+        match side {
+            Side::Left => self.left,
+            Side::Right => self.right,
+        }
+    }
+
+    /// The target of a `$ref`, `Err` if it can't be found or has keywords next to it
+    fn resolve<'p>(&self, property: &'p Property, side: Side) -> Result<&'p Property, ()>
+    where
+        's: 'p,
+    {
+        let reference = match property {
+            Property::Ref(reference) => reference,
+            property => return Ok(property),
+        };
+        let schema = self.schema(side);
+        let siblings_apply = schema
+            .draft()
+            .is_none_or(|draft| draft >= Draft::Draft201909);
+        if siblings_apply && !is_unconstrained(&property.constraints()) {
+            return Err(());
+        }
+        reference.resolve(schema).map_err(drop)
+    }
+
+    /// Whether every instance of `left` is one of `right`
+    fn subset(&mut self, left: &Property, right: &Property) -> Compatibility {
+        if same(left, right) {
+            return Compatibility::Compatible;
+        }
+        if self.depth >= MAX_DEPTH {
+            return Compatibility::Unknown;
+        }
+        self.depth += 1;
+        let answer = match (
+            self.resolve(left, Side::Left),
+            self.resolve(right, Side::Right),
+        ) {
+            (Ok(left), Ok(right)) => self.resolved(left, right),
+            _ => Compatibility::Unknown,
+        };
+        self.depth -= 1;
+        answer
+    }
+
+    fn resolved(&mut self, left: &Property, right: &Property) -> Compatibility {
+        if left.is_false() {
+            return Compatibility::Compatible;
+        }
+        let (left_constraints, right_constraints) = (left.constraints(), right.constraints());
+        match right {
+            Property::Boolean(true) => return Compatibility::Compatible,
+            Property::Boolean(false) if is_unconstrained(&left_constraints) => {
+                return Compatibility::Incompatible
+            }
+            Property::Boolean(false) => return Compatibility::Unknown,
+            _ if is_unconstrained(&right_constraints) => return Compatibility::Compatible,
+            _ => {}
+        }
+
+        // the instances of `right` are those of each of its `allOf` and of any of its `anyOf`
+        if let Some(branches) = &right_constraints.applicators.all_of {
+            let mut rest = right.clone();
+            rest.applicators_mut().all_of = None;
+            let mut answer = self.subset(left, &rest);
+            for branch in branches {
+                answer = answer.and(self.subset(left, branch));
+            }
+            return answer;
+        }
+        if let Some(branches) = &right_constraints.applicators.any_of {
+            let mut rest = right.clone();
+            rest.applicators_mut().any_of = None;
+            let rest = self.subset(left, &rest);
+            let mut any = false;
+            for branch in branches {
+                any = any || self.subset(left, branch) == Compatibility::Compatible;
+            }
+            return rest.and(match any {
+                true => Compatibility::Compatible,
+                false => Compatibility::Unknown,
+            });
+        }
+
+        // the instances of `left` are those of each of its branches together with the rest
+        let applicators = left_constraints.applicators;
+        for (keyword, branches) in [
+            ("allOf", &applicators.all_of),
+            ("anyOf", &applicators.any_of),
+            ("oneOf", &applicators.one_of),
+        ] {
+            let Some(branches) = branches else {
+                continue;
+            };
+            let mut rest = left.clone();
+            let rest_applicators = rest.applicators_mut();
+            match keyword {
+                "allOf" => rest_applicators.all_of = None,
+                "anyOf" => rest_applicators.any_of = None,
+                _ => rest_applicators.one_of = None,
+            }
+            if keyword == "allOf" {
+                let merged = branches
+                    .iter()
+                    .try_fold(rest, |merged, branch| merge(&merged, branch));
+                return match merged {
+                    Some(merged) => self.subset(&merged, right),
+                    None => Compatibility::Unknown,
+                };
+            }
+            let mut answers = Vec::new();
+            for branch in branches {
+                answers.push(match merge(&rest, branch) {
+                    Some(merged) => self.subset(&merged, right),
+                    None => Compatibility::Unknown,
+                });
+            }
+            return match (keyword, Compatibility::all(answers)) {
+                // the counterexample of one branch may match another one as well
+                ("oneOf", Compatibility::Incompatible) => Compatibility::Unknown,
+                (_, answer) => answer,
+            };
+        }
+
+        self.keywords(left, right)
+    }
+
+    fn keywords(&mut self, left_property: &Property, right_property: &Property) -> Compatibility {
+        let (left, right) = (left_property.constraints(), right_property.constraints());
+        // keywords of `right` that are not reasoned about have to be the same in `left`
+        if *right.applicators != Applicators::default() && right.applicators != left.applicators {
+            return Compatibility::Unknown;
+        }
+        let compared = |keyword: &str| COUNTED.contains(&keyword) || keyword == "uniqueItems";
+        if right
+            .extra
+            .iter()
+            .any(|(keyword, value)| !compared(keyword) && left.extra.get(keyword) != Some(value))
+        {
+            return Compatibility::Unknown;
+        }
+        if right.object.pattern_properties.is_some()
+            && right.object.pattern_properties != left.object.pattern_properties
+        {
+            return Compatibility::Unknown;
+        }
+        // keywords that constrain `left` further may rule out any counterexample
+        let opaque = *left.applicators != Applicators::default()
+            || left
+                .extra
+                .keys()
+                .any(|keyword| !COUNTED.contains(&keyword.as_str()))
+            || left.object.pattern_properties.is_some()
+            || left.string.is_some_and(|string| string.pattern.is_some())
+            || left.format.is_some()
+            || left
+                .number
+                .is_some_and(|number| number.multiple_of.is_some());
+
+        if let Some(values) = values(&left) {
+            return Compatibility::all(
+                values
+                    .into_iter()
+                    .map(|value| self.instance(left_property, right_property, value)),
+            );
+        }
+
+        let right_types = types(&right);
+        let mut answer = Compatibility::Compatible;
+        for name in types(&left) {
+            answer = answer.and(match inhabited(&left, name) {
+                Ok(false) => Compatibility::Compatible,
+                Err(_) => Compatibility::Unknown,
+                Ok(true) if !allows_type(&right_types, name) => Compatibility::Incompatible,
+                Ok(true) => match values(&right) {
+                    Some(values) if values.iter().all(|value| type_of(value) != name) => {
+                        Compatibility::Incompatible
+                    }
+                    // few enough instances to look at each of them
+                    Some(_) if name == "null" || name == "boolean" => Compatibility::all(
+                        [Value::Null, Value::Bool(false), Value::Bool(true)]
+                            .iter()
+                            .filter(|value| type_of(value) == name)
+                            .map(|value| self.instance(left_property, right_property, value)),
+                    ),
+                    Some(_) => Compatibility::Unknown,
+                    None => self.within(&left, &right, name),
+                },
+            });
+        }
+        match (answer, opaque) {
+            (Compatibility::Incompatible, true) => Compatibility::Unknown,
+            (answer, _) => answer,
+        }
+    }
+
+    /// Whether the instances of type `name` that `left` allows are allowed by `right`
+    fn within(&mut self, left: &Constraints, right: &Constraints, name: &str) -> Compatibility {
+        let mut answer = match (range(left, name), range(right, name)) {
+            (Ok(left), Ok(right)) => Compatibility::from_bool(covers(right, left)),
+            _ => return Compatibility::Unknown,
+        };
+
+        // these are only satisfied by the same value
+        let differs = match name {
+            "string" => {
+                let (left_pattern, right_pattern) = (
+                    left.string.and_then(|c| c.pattern.as_ref()),
+                    right.string.and_then(|c| c.pattern.as_ref()),
+                );
+                (right_pattern.is_some() && left_pattern != right_pattern)
+                    || (right.format.is_some() && left.format != right.format)
+            }
+            "array" => {
+                right.extra.contains_key("uniqueItems")
+                    && left.extra.get("uniqueItems") != right.extra.get("uniqueItems")
+            }
+            _ => false,
+        };
+        if differs {
+            answer = answer.and(Compatibility::Unknown);
+        }
+        let multiple_of = |c: &Constraints| {
+            c.number
+                .and_then(|number| number.multiple_of.as_ref())
+                .and_then(Value::as_f64)
+        };
+        if let ("integer" | "number", Some(divisor)) = (name, multiple_of(right)) {
+            let divides =
+                |value: Option<f64>| value.is_some_and(|value| (value / divisor).fract() == 0.0);
+            // every integer is a multiple of e.g. `0.5`
+            let divided = divides(multiple_of(left)) || (name == "integer" && divides(Some(1.0)));
+            if !divided {
+                answer = answer.and(Compatibility::Unknown);
+            }
+        }
+
+        match name {
+            "array" => answer.and(self.items(left, right)),
+            "object" => answer.and(self.object(left, right)),
+            _ => answer,
+        }
+    }
+
+    fn items(&mut self, left: &Constraints, right: &Constraints) -> Compatibility {
+        // the left side has no elements past its `maxItems` to check
+        let max = match range(left, "array") {
+            Ok((_, Some(max))) => max.value,
+            _ => f64::INFINITY,
+        };
+        match (left.items, right.items) {
+            (_, None) => Compatibility::Compatible,
+            _ if max == 0.0 => Compatibility::Compatible,
+            (_, Some(ItemsOf::Positional(_))) => Compatibility::Unknown,
+            (Some(ItemsOf::Positional(items)), Some(ItemsOf::Each(right))) => {
+                // elements past the positional ones are only checked by `additionalItems`
+                let rest = match left.extra.get("additionalItems") {
+                    _ if max <= items.len() as f64 => Compatibility::Compatible,
+                    Some(_) => Compatibility::Unknown,
+                    None => self.subset(&ANYTHING, right),
+                };
+                let items = Compatibility::all(
+                    items
+                        .iter()
+                        .take(max.min(items.len() as f64) as usize)
+                        .map(|left| self.subset(left, right))
+                        .collect::<Vec<_>>(),
+                );
+                items.and(rest)
+            }
+            (left, Some(ItemsOf::Each(right))) => {
+                let left = match left {
+                    Some(ItemsOf::Each(left)) => left,
+                    _ => &ANYTHING,
+                };
+                self.subset(left, right)
+            }
+        }
+    }
+
+    fn object(&mut self, left: &Constraints, right: &Constraints) -> Compatibility {
+        let (left, right) = (&left.object, &right.object);
+        if left.pattern_properties.is_some() || right.pattern_properties.is_some() {
+            return Compatibility::Unknown;
+        }
+        let mut answer = if right
+            .required
+            .iter()
+            .all(|name| left.required.contains(name))
+        {
+            Compatibility::Compatible
+        } else if left.required.iter().all(|name| {
+            let subschema = left
+                .properties
+                .and_then(|properties| properties.get(name))
+                .or(left.additional_properties)
+                .unwrap_or(&ANYTHING);
+            self.certainly_inhabited(subschema)
+        }) {
+            // an object with just the properties `left` requires is missing one of `right`
+            Compatibility::Incompatible
+        } else {
+            Compatibility::Unknown
+        };
+
+        let left_additional = left.additional_properties.unwrap_or(&ANYTHING);
+        let right_additional = right.additional_properties.unwrap_or(&ANYTHING);
+        for (name, right) in right.properties.into_iter().flatten() {
+            let left = left
+                .properties
+                .and_then(|properties| properties.get(name))
+                .unwrap_or(left_additional);
+            answer = answer.and(self.subset(left, right));
+        }
+        for (name, left) in left.properties.into_iter().flatten() {
+            if right
+                .properties
+                .is_none_or(|properties| !properties.contains_key(name))
+            {
+                answer = answer.and(self.subset(left, right_additional));
+            }
+        }
+        answer.and(self.subset(left_additional, right_additional))
+    }
+
+    /// Whether some instance of a subschema of `left` is known, without looking into objects and
+    /// arrays
+    fn certainly_inhabited(&self, property: &Property) -> bool {
+        let property = match self.resolve(property, Side::Left) {
+            Ok(property) => property,
+            Err(_) => return false,
+        };
+        if let Property::Boolean(accepts) = property {
+            return *accepts;
+        }
+        let constraints = property.constraints();
+        is_unconstrained(&constraints)
+            || (*constraints.applicators == Applicators::default()
+                && constraints.extra.is_empty()
+                && values(&constraints).is_none()
+                && constraints.format.is_none()
+                && constraints
+                    .string
+                    .is_none_or(|string| string.pattern.is_none())
+                && constraints
+                    .number
+                    .is_none_or(|number| number.multiple_of.is_none())
+                && types(&constraints).into_iter().any(|name| {
+                    name != "object" && name != "array" && inhabited(&constraints, name) == Ok(true)
+                }))
+    }
+
+    /// Whether `value` is no instance of `left` or one of `right` as well
+    fn instance(&self, left: &Property, right: &Property, value: &Value) -> Compatibility {
+        match (
+            self.accepts(left, Side::Left, value),
+            self.accepts(right, Side::Right, value),
+        ) {
+            (Compatibility::Incompatible, _) | (_, Compatibility::Compatible) => {
+                Compatibility::Compatible
+            }
+            (Compatibility::Compatible, Compatibility::Incompatible) => Compatibility::Incompatible,
+            _ => Compatibility::Unknown,
+        }
+    }
+
+    /// Whether the subschema accepts `value`, by validating it
+    fn accepts(&self, property: &Property, side: Side, value: &Value) -> Compatibility {
+        let options = ValidationOptions::default();
+        let mut context = Context::new(Some(self.schema(side)), &options);
+        match property.validate_in(value, &mut context) {
+            Err(_) => Compatibility::Incompatible,
+            // it may still reject `value`
+            Ok(_) if undecided(property) => Compatibility::Unknown,
+            Ok(_) => Compatibility::Compatible,
+        }
+    }
+}
+
+impl Schema {
+    /// Whether every instance this schema accepts is accepted by `other` as well.
+    ///
+    /// This is conservative: it reasons about types, numeric ranges, lengths, `required`,
+    /// `properties`, `additionalProperties` and enumerations, and answers
+    /// [`Compatibility::Unknown`] wherever that isn't enough, e.g. for differing `pattern`s, `not`
+    /// or `$ref`s into other documents.
+    ///
+    /// ```
+    /// use serde_json::json;
+    /// use serde_json_schema::{Compatibility, Schema, TryFrom};
+    ///
+    /// let port = Schema::try_from(json!({"type": "integer", "minimum": 1, "maximum": 65535})).unwrap();
+    /// let number = Schema::try_from(json!({"type": "number", "minimum": 0})).unwrap();
+    /// assert_eq!(port.is_subset_of(&number), Compatibility::Compatible);
+    /// assert_eq!(number.is_subset_of(&port), Compatibility::Incompatible);
+    /// ```
+    pub fn is_subset_of(&self, other: &Schema) -> Compatibility {
+        let (left, right) = match (self.as_property(), other.as_property()) {
+            (Some(left), Some(right)) => (left, right),
+            _ => return Compatibility::Unknown,
+        };
+        let mut subset = Subset {
+            left: self,
+            right: other,
+            depth: 0,
+        };
+        subset.subset(left, right)
+    }
+}
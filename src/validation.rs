@@ -1,8 +1,743 @@
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Number, Value};
 
-/// Number validation Criteria (WIP)
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::sync::Arc;
+
+use crate::annotation::{present, AnnotationMap, Annotations, LocationAnnotations};
+use crate::content;
+use crate::draft::Draft;
+use crate::format::FormatChecker;
+use crate::keyword::{self, Keyword, KeywordCompiler};
+use crate::resolver::Resolver;
+use crate::Schema;
+
+/// Bounds of a number.
+///
+/// Bounds that aren't numbers are ignored, as are `exclusiveMinimum` and `exclusiveMaximum` of
+/// `true`, which make `minimum` and `maximum` exclusive in draft 4.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct NumberCriteria {
-    exclusive_minimum: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) minimum: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) maximum: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) exclusive_minimum: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) exclusive_maximum: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) multiple_of: Option<Value>,
+}
+
+/// Length and `pattern` of a string, the length counts characters rather than bytes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StringCriteria {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) min_length: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_length: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) pattern: Option<String>,
+}
+
+/// Number of elements of an array
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArrayCriteria {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) min_items: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) max_items: Option<Value>,
+}
+
+/// `enum` and `const`, which apply to instances of any type
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct ValueCriteria {
+    #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub(crate) enum_values: Option<Vec<Value>>,
+    /// `Some(Value::Null)` if the constant is `null`
+    #[serde(
+        rename = "const",
+        default,
+        deserialize_with = "present",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub(crate) const_value: Option<Value>,
+}
+
+/// Compares numbers exactly if both are integers
+fn compare(left: &Number, right: &Number) -> Option<Ordering> {
+    match (left.as_i64(), right.as_i64(), left.as_u64(), right.as_u64()) {
+        (Some(left), Some(right), _, _) => Some(left.cmp(&right)),
+        (_, _, Some(left), Some(right)) => Some(left.cmp(&right)),
+        _ => left.as_f64()?.partial_cmp(&right.as_f64()?),
+    }
+}
+
+/// Whether two instances are equal, `1` and `1.0` being the same number
+pub(crate) fn equal(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => {
+            compare(left, right) == Some(Ordering::Equal)
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len() && left.iter().zip(right).all(|(l, r)| equal(l, r))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, l)| right.get(key).is_some_and(|r| equal(l, r)))
+        }
+        _ => left == right,
+    }
+}
+
+fn is_multiple(number: &Number, factor: &Number) -> bool {
+    if let (Some(number), Some(factor)) = (number.as_i64(), factor.as_i64()) {
+        return factor == 0 || number % factor == 0;
+    }
+    match (number.as_f64(), factor.as_f64()) {
+        (Some(number), Some(factor)) if factor > 0.0 => {
+            let quotient = number / factor;
+            // a quotient too large to tell apart from an integer can't be checked
+            quotient.is_finite() && (quotient - quotient.round()).abs() < 1e-9
+        }
+        _ => true,
+    }
+}
+
+/// A count like `minLength`, which may be given as e.g. `2.0`
+fn count(value: &Option<Value>) -> Option<u64> {
+    match value.as_ref()? {
+        Value::Number(number) => number.as_u64().or_else(|| {
+            number
+                .as_f64()
+                .filter(|n| n.fract() == 0.0 && *n >= 0.0)
+                .map(|n| n as u64)
+        }),
+        _ => None,
+    }
+}
+
+impl NumberCriteria {
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Vec<String> {
+        let Value::Number(number) = json else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        let exclusive = |bound: &Option<Value>| matches!(bound, Some(Value::Bool(true)));
+        let mut check = |bound: &Option<Value>, failing: &[Ordering], relation: &str| {
+            if let Some(Value::Number(bound)) = bound {
+                if compare(number, bound).is_some_and(|ordering| failing.contains(&ordering)) {
+                    errors.push(context.error(format!("{} is {} {}", number, relation, bound)));
+                }
+            }
+        };
+        if exclusive(&self.exclusive_minimum) {
+            check(
+                &self.minimum,
+                &[Ordering::Less, Ordering::Equal],
+                "not above",
+            );
+        } else {
+            check(&self.minimum, &[Ordering::Less], "below the minimum of");
+        }
+        if exclusive(&self.exclusive_maximum) {
+            check(
+                &self.maximum,
+                &[Ordering::Greater, Ordering::Equal],
+                "not below",
+            );
+        } else {
+            check(&self.maximum, &[Ordering::Greater], "above the maximum of");
+        }
+        check(
+            &self.exclusive_minimum,
+            &[Ordering::Less, Ordering::Equal],
+            "not above",
+        );
+        check(
+            &self.exclusive_maximum,
+            &[Ordering::Greater, Ordering::Equal],
+            "not below",
+        );
+        if let Some(Value::Number(factor)) = &self.multiple_of {
+            if !is_multiple(number, factor) {
+                errors.push(context.error(format!("{} is not a multiple of {}", number, factor)));
+            }
+        }
+        errors
+    }
+}
+
+impl StringCriteria {
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Vec<String> {
+        let Value::String(string) = json else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        let length = string.chars().count() as u64;
+        if let Some(min) = count(&self.min_length).filter(|min| length < *min) {
+            errors.push(context.error(format!("{:?} is shorter than {} characters", string, min)));
+        }
+        if let Some(max) = count(&self.max_length).filter(|max| length > *max) {
+            errors.push(context.error(format!("{:?} is longer than {} characters", string, max)));
+        }
+        if let Some(pattern) = &self.pattern {
+            match context
+                .regex(pattern)
+                .and_then(|regex| regex.is_match(string))
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    errors.push(context.error(format!("{:?} does not match {:?}", string, pattern)))
+                }
+                Err(error) => errors.push(context.error(error)),
+            }
+        }
+        errors
+    }
+}
+
+impl ArrayCriteria {
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Vec<String> {
+        let Value::Array(elements) = json else {
+            return Vec::new();
+        };
+        let mut errors = Vec::new();
+        let length = elements.len() as u64;
+        if let Some(min) = count(&self.min_items).filter(|min| length < *min) {
+            errors.push(context.error(format!("array has fewer than {} items", min)));
+        }
+        if let Some(max) = count(&self.max_items).filter(|max| length > *max) {
+            errors.push(context.error(format!("array has more than {} items", max)));
+        }
+        errors
+    }
+}
+
+impl ValueCriteria {
+    pub(crate) fn validate_in(&self, json: &Value, context: &mut Context) -> Vec<String> {
+        let mut errors = Vec::new();
+        if let Some(values) = &self.enum_values {
+            if !values.iter().any(|value| equal(value, json)) {
+                errors.push(context.error(format!("{} is not one of {:?}", json, values)));
+            }
+        }
+        if let Some(value) = &self.const_value {
+            if !equal(value, json) {
+                errors.push(context.error(format!("{} is not {}", json, value)));
+            }
+        }
+        errors
+    }
+}
+
+/// Result of validating an instance location against a subschema
+pub(crate) type Outcome = Result<Evaluated, Vec<String>>;
+
+/// Parts of an instance location that a subschema evaluated,
+/// which `unevaluatedProperties` and `unevaluatedItems` have to skip
+#[derive(Debug, Default)]
+pub(crate) struct Evaluated {
+    pub properties: BTreeSet<String>,
+    pub items: BTreeSet<usize>,
+}
+
+impl Evaluated {
+    pub fn merge(&mut self, other: Evaluated) {
+        self.properties.extend(other.properties);
+        self.items.extend(other.items);
+    }
+}
+
+/// Accumulates the outcomes of all keywords of a subschema
+#[derive(Debug, Default)]
+pub(crate) struct Report {
+    pub evaluated: Evaluated,
+    pub errors: Vec<String>,
+}
+
+impl Report {
+    pub fn add(&mut self, outcome: Outcome) {
+        match outcome {
+            Ok(evaluated) => self.evaluated.merge(evaluated),
+            Err(errors) => self.errors.extend(errors),
+        }
+    }
+
+    pub fn error(&mut self, message: String) {
+        self.errors.push(message);
+    }
+
+    pub fn into_outcome(self) -> Outcome {
+        if self.errors.is_empty() {
+            Ok(self.evaluated)
+        } else {
+            Err(self.errors)
+        }
+    }
+}
+
+/// The implementation used for `pattern` and `patternProperties`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RegexEngine {
+    /// The [`regex`](https://docs.rs/regex) crate, which runs in linear time but supports neither
+    /// lookaround nor backreferences
+    #[default]
+    Regex,
+    /// The [`fancy-regex`](https://docs.rs/fancy-regex) crate, which backtracks and supports
+    /// lookaround and backreferences like ECMA 262 does
+    #[cfg(feature = "fancy-regex")]
+    FancyRegex,
+}
+
+/// A compiled `pattern`
+#[derive(Debug, Clone)]
+pub(crate) enum Pattern {
+    Regex(regex::Regex),
+    #[cfg(feature = "fancy-regex")]
+    FancyRegex(fancy_regex::Regex),
+}
+
+impl Pattern {
+    fn new(engine: RegexEngine, pattern: &str) -> Result<Pattern, String> {
+        match engine {
+            RegexEngine::Regex => regex::Regex::new(pattern)
+                .map(Pattern::Regex)
+                .map_err(|e| e.to_string()),
+            #[cfg(feature = "fancy-regex")]
+            RegexEngine::FancyRegex => fancy_regex::Regex::new(pattern)
+                .map(Pattern::FancyRegex)
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    pub fn is_match(&self, haystack: &str) -> Result<bool, String> {
+        match self {
+            Pattern::Regex(regex) => Ok(regex.is_match(haystack)),
+            #[cfg(feature = "fancy-regex")]
+            Pattern::FancyRegex(regex) => regex.is_match(haystack).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Configures how [`Schema::validate_with`] validates an instance.
+///
+/// ```
+/// # use serde_json_schema::{draft::Draft, ValidationOptions};
+/// let options = ValidationOptions::new()
+///     .draft(Draft::Draft07)
+///     .assert_formats(true)
+///     .max_errors(10);
+/// ```
+#[derive(Clone, Default)]
+pub struct ValidationOptions {
+    draft: Option<Draft>,
+    assert_formats: bool,
+    assert_content: bool,
+    strict_formats: bool,
+    max_errors: Option<usize>,
+    fail_fast: bool,
+    regex_engine: RegexEngine,
+    resolver: Option<Arc<dyn Resolver>>,
+    formats: HashMap<String, FormatChecker>,
+    keywords: HashMap<String, KeywordCompiler>,
+}
+
+impl fmt::Debug for ValidationOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ValidationOptions")
+            .field("draft", &self.draft)
+            .field("assert_formats", &self.assert_formats)
+            .field("assert_content", &self.assert_content)
+            .field("strict_formats", &self.strict_formats)
+            .field("max_errors", &self.max_errors)
+            .field("fail_fast", &self.fail_fast)
+            .field("regex_engine", &self.regex_engine)
+            .field("resolver", &self.resolver.is_some())
+            .field("formats", &self.formats.keys().collect::<Vec<_>>())
+            .field("keywords", &self.keywords.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl ValidationOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Validates as `draft` requires, regardless of the `$schema` of the schema.
+    ///
+    /// Before 2019-09 keywords next to `$ref` are ignored and `unevaluatedProperties` and
+    /// `unevaluatedItems` don't exist. Schemas without either are validated as the latest draft.
+    pub fn draft(mut self, draft: Draft) -> Self {
+        self.draft = Some(draft);
+        self
+    }
+
+    /// Stops validating once `max` errors were found
+    pub fn max_errors(mut self, max: usize) -> Self {
+        self.max_errors = Some(max);
+        self
+    }
+
+    /// Stops validating at the first error
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Chooses the implementation of `pattern` and `patternProperties`
+    pub fn regex_engine(mut self, engine: RegexEngine) -> Self {
+        self.regex_engine = engine;
+        self
+    }
+
+    /// Resolves references to other documents with `resolver`, see [`Schema::bundle`]
+    pub fn resolver(mut self, resolver: impl Resolver + 'static) -> Self {
+        self.resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    pub(crate) fn external_resolver(&self) -> Option<&dyn Resolver> {
+        self.resolver.as_deref()
+    }
+
+    /// The number of errors after which validation stops, if any
+    pub(crate) fn error_limit(&self) -> Option<usize> {
+        if self.fail_fast {
+            Some(1)
+        } else {
+            self.max_errors
+        }
+    }
+
+    /// Treats `format` as an assertion instead of an annotation.
+    ///
+    /// Strings are then checked against the [known formats](crate::format::KNOWN_FORMATS) and
+    /// those registered with [`ValidationOptions::format`], other formats are ignored unless
+    /// [`ValidationOptions::strict_formats`] is enabled.
+    pub fn assert_formats(mut self, assert: bool) -> Self {
+        self.assert_formats = assert;
+        self
+    }
+
+    /// Reports formats that are neither known nor registered, instead of ignoring them.
+    ///
+    /// Only has an effect if formats are asserted. Like any `format`, an unknown one is reported
+    /// for string instances, whatever `type` the subschema declares.
+    pub fn strict_formats(mut self, strict: bool) -> Self {
+        self.strict_formats = strict;
+        self
+    }
+
+    /// Registers a checker for the format `name`, it takes precedence over a known format of the
+    /// same name.
+    ///
+    /// ```
+    /// # use serde_json_schema::ValidationOptions;
+    /// let options = ValidationOptions::new()
+    ///     .assert_formats(true)
+    ///     .format("semver", |value| value.split('.').count() == 3);
+    /// ```
+    pub fn format(
+        mut self,
+        name: impl Into<String>,
+        check: impl Fn(&str) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.formats.insert(name.into(), Arc::new(check));
+        self
+    }
+
+    /// Treats `contentEncoding`, `contentMediaType` and `contentSchema` as assertions.
+    ///
+    /// Strings then have to decode, `application/json` content has to parse and is validated
+    /// against the `contentSchema`. Its errors start with their location within the instance,
+    /// e.g. `/payload/id`.
+    pub fn assert_content(mut self, assert: bool) -> Self {
+        self.assert_content = assert;
+        self
+    }
+
+    /// Validates the keyword `name` with `K`, wherever it appears in the schema
+    pub fn keyword<K: Keyword + 'static>(mut self, name: impl Into<String>) -> Self {
+        self.keywords.insert(name.into(), keyword::compiler::<K>());
+        self
+    }
+
+    /// Checks `value` against `format`, if formats are asserted
+    pub(crate) fn check_format(&self, format: &str, value: &str) -> Result<(), String> {
+        if !self.assert_formats {
+            return Ok(());
+        }
+        let valid = match self.formats.get(format) {
+            Some(check) => Some(check(value)),
+            None => crate::format::check(format, value),
+        };
+        match valid {
+            Some(true) => Ok(()),
+            Some(false) => Err(format!("{:?} is not a valid {:?}", value, format)),
+            None if self.strict_formats => Err(format!("unknown format {:?}", format)),
+            None => {
+                log::debug!("ignoring unknown format {:?}", format);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// State that is threaded through a single validation run
+pub(crate) struct Context<'a> {
+    /// The document that `$ref`s are resolved against
+    pub root: Option<&'a Schema>,
+    pub options: &'a ValidationOptions,
+    /// The draft whose rules apply, `None` for the latest
+    draft: Option<Draft>,
+    instance_path: Vec<String>,
+    /// `$ref`s currently being validated, together with the instance location they were entered at
+    active_refs: Vec<(String, String)>,
+    /// Only collected when asked for
+    annotations: Option<AnnotationMap>,
+    regexes: HashMap<String, Pattern>,
+    /// Compiled keywords by their name and serialized value
+    keywords: HashMap<(String, String), Result<Arc<dyn Keyword>, String>>,
+    /// Set while validating decoded content, see [`Context::error`]
+    in_content: bool,
+    /// Errors found so far, to stop at [`ValidationOptions::max_errors`]
+    errors: usize,
+}
+
+impl<'a> Context<'a> {
+    pub fn new(root: Option<&'a Schema>, options: &'a ValidationOptions) -> Self {
+        Context {
+            root,
+            options,
+            draft: options.draft.or_else(|| root.and_then(Schema::draft)),
+            instance_path: Vec::new(),
+            active_refs: Vec::new(),
+            annotations: None,
+            regexes: HashMap::new(),
+            keywords: HashMap::new(),
+            in_content: false,
+            errors: 0,
+        }
+    }
+
+    /// A context that also collects annotations
+    pub fn annotating(root: Option<&'a Schema>, options: &'a ValidationOptions) -> Self {
+        Context {
+            annotations: Some(AnnotationMap::new()),
+            ..Context::new(root, options)
+        }
+    }
+
+    pub fn into_annotations(self) -> AnnotationMap {
+        self.annotations.unwrap_or_default()
+    }
+
+    fn location(&mut self) -> Option<&mut LocationAnnotations> {
+        let pointer = self.instance_pointer();
+        self.annotations
+            .as_mut()
+            .map(|annotations| annotations.entry(pointer).or_default())
+    }
+
+    /// Records the annotations of a subschema that applies to the current location
+    pub fn annotate(&mut self, annotations: &Annotations) {
+        if *annotations == Annotations::default() {
+            return;
+        }
+        if let Some(location) = self.location() {
+            location.schemas.push(annotations.clone());
+        }
+    }
+
+    /// Runs `f`, but forgets the annotations it collected if it fails
+    pub fn attempt<T, E>(&mut self, f: impl FnOnce(&mut Self) -> Result<T, E>) -> Result<T, E> {
+        let snapshot = (self.annotations.clone(), self.errors);
+        let result = f(self);
+        if result.is_err() {
+            (self.annotations, self.errors) = snapshot;
+        }
+        result
+    }
+
+    /// Runs `f` and forgets the annotations and errors it collected in any case, as for `not`
+    pub fn discarding<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        let snapshot = (self.annotations.clone(), self.errors);
+        let result = f(self);
+        (self.annotations, self.errors) = snapshot;
+        result
+    }
+
+    /// Whether enough errors were found to stop validating
+    pub fn done(&self) -> bool {
+        self.options
+            .error_limit()
+            .is_some_and(|limit| self.errors >= limit)
+    }
+
+    /// Whether the rules of `draft` or a later one apply
+    pub fn draft_at_least(&self, draft: Draft) -> bool {
+        self.draft.is_none_or(|current| current >= draft)
+    }
+
+    /// Compiles `pattern` once per validation run
+    pub fn regex(&mut self, pattern: &str) -> Result<Pattern, String> {
+        if let Some(regex) = self.regexes.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Pattern::new(self.options.regex_engine, pattern)
+            .map_err(|e| format!("invalid pattern {:?}: {}", pattern, e))?;
+        self.regexes.insert(pattern.to_owned(), regex.clone());
+        Ok(regex)
+    }
+
+    /// Validates `json` with the registered keywords among `extra`, the unknown keywords of a subschema
+    pub fn apply_keywords(&mut self, extra: &Map<String, Value>, json: &Value) -> Vec<String> {
+        let mut errors = Vec::new();
+        for (name, value) in extra {
+            let Some(compile) = self.options.keywords.get(name) else {
+                continue;
+            };
+            let compiled = self
+                .keywords
+                .entry((name.clone(), value.to_string()))
+                .or_insert_with(|| {
+                    compile(value)
+                        .map_err(|e| format!("invalid value for keyword {:?}: {}", name, e))
+                });
+            let result = match compiled {
+                Ok(keyword) => keyword.validate(json).map_err(|error| error.to_string()),
+                Err(error) => Err(error.clone()),
+            };
+            if let Err(error) = result {
+                errors.push(self.error(error));
+            }
+        }
+        errors
+    }
+
+    /// Decodes and validates the content of a string, if content is asserted
+    pub fn check_content(&mut self, annotations: &Annotations, json: &Value) -> Vec<String> {
+        let Value::String(content) = json else {
+            return Vec::new();
+        };
+        if !self.options.assert_content {
+            return Vec::new();
+        }
+        let decoded = match &annotations.content_encoding {
+            Some(encoding) => match content::decode(encoding, content) {
+                Ok(decoded) => decoded,
+                Err(error) => {
+                    return vec![self.error(format!(
+                        "{:?} is not valid {}: {}",
+                        content, encoding, error
+                    ))]
+                }
+            },
+            None => content.as_bytes().to_vec(),
+        };
+        let media_type = match &annotations.content_media_type {
+            Some(media_type) if content::is_json(media_type) => media_type,
+            _ => return Vec::new(),
+        };
+        let document = match content::parse_json(&decoded) {
+            Ok(document) => document,
+            Err(error) => {
+                return vec![self.error(format!("content is not valid {}: {}", media_type, error))]
+            }
+        };
+        match &annotations.content_schema {
+            Some(schema) => {
+                let mut nested = Context {
+                    draft: self.draft,
+                    instance_path: self.instance_path.clone(),
+                    in_content: true,
+                    errors: self.errors,
+                    ..Context::new(self.root, self.options)
+                };
+                let result = schema.validate_in(&document, &mut nested);
+                self.errors = nested.errors;
+                result.err().unwrap_or_default()
+            }
+            None => Vec::new(),
+        }
+    }
+
+    /// Turns `message` into an error at the current location.
+    ///
+    /// Errors within decoded content are prefixed with their location, they could not be told
+    /// apart from errors of the surrounding instance otherwise.
+    pub fn error(&mut self, message: String) -> String {
+        self.errors += 1;
+        if self.in_content {
+            format!("{}: {}", self.instance_pointer(), message)
+        } else {
+            message
+        }
+    }
+
+    pub fn evaluated_property(&mut self, name: &str) {
+        if let Some(location) = self.location() {
+            location.evaluated_properties.push(name.to_owned());
+        }
+    }
+
+    pub fn evaluated_item(&mut self, index: usize) {
+        if let Some(location) = self.location() {
+            location.evaluated_items.push(index);
+        }
+    }
+
+    /// JSON Pointer to the part of the instance that is currently being validated
+    pub fn instance_pointer(&self) -> String {
+        self.instance_path
+            .iter()
+            .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+            .collect()
+    }
+
+    /// Runs `f` one level deeper into the instance
+    pub fn descend<T>(&mut self, segment: impl ToString, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.instance_path.push(segment.to_string());
+        let result = f(self);
+        self.instance_path.pop();
+        result
+    }
+
+    /// Runs `f` while `reference` is being followed.
+    ///
+    /// Entering the same reference twice at the same instance location means that validation
+    /// would recurse forever without consuming any of the instance, so this is reported as an
+    /// error instead. Recursive schemas still work, as long as every cycle descends into the
+    /// instance.
+    pub fn follow<T>(
+        &mut self,
+        reference: &str,
+        f: impl FnOnce(&mut Self) -> Result<T, Vec<String>>,
+    ) -> Result<T, Vec<String>> {
+        let location = self.instance_pointer();
+        if self
+            .active_refs
+            .iter()
+            .any(|(active, at)| active == reference && *at == location)
+        {
+            return Err(vec![self.error(format!(
+                "reference cycle: {:?} was entered twice at instance location {:?}",
+                reference, location
+            ))]);
+        }
+        self.active_refs.push((reference.to_owned(), location));
+        let result = f(self);
+        self.active_refs.pop();
+        result
+    }
 }
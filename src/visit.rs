@@ -0,0 +1,242 @@
+//! Walking every subschema of a document
+//!
+//! ```
+//! use serde_json::json;
+//! use serde_json_schema::property::Property;
+//! use serde_json_schema::visit::Location;
+//! use serde_json_schema::{Schema, TryFrom};
+//!
+//! let schema = Schema::try_from(json!({
+//!     "type": "object",
+//!     "properties": { "tags": { "type": "array", "items": { "type": "string" } } }
+//! }))
+//! .unwrap();
+//!
+//! let mut pointers = Vec::new();
+//! schema.walk(&mut |_: &Property, location: &Location| pointers.push(location.pointer.clone()));
+//! assert_eq!(pointers, ["", "/properties/tags", "/properties/tags/items"]);
+//! ```
+
+use crate::property::{Property, RefProperty};
+use crate::{Schema, SchemaDefinition, SchemaInner, SchemaMap};
+
+/// Where a subschema is found within the document
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    /// JSON Pointer of the subschema, e.g. `/properties/name`.
+    ///
+    /// For the target of a followed `$ref` this is the fragment of the reference, or the whole
+    /// reference if it points into another document.
+    pub pointer: String,
+
+    /// The keyword the subschema is found under, e.g. `properties`, `allOf` or `$defs`.
+    ///
+    /// `$ref` for the target of a followed reference, `None` for the root.
+    pub keyword: Option<&'static str>,
+
+    /// How many subschemas are above this one, `0` for the root
+    pub depth: usize,
+}
+
+impl Location {
+    fn root() -> Location {
+        Location {
+            pointer: String::new(),
+            keyword: None,
+            depth: 0,
+        }
+    }
+
+    fn child(&self, keyword: &'static str, key: Option<&str>) -> Location {
+        let mut pointer = format!("{}/{}", self.pointer, keyword);
+        if let Some(key) = key {
+            pointer.push('/');
+            pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+        }
+        Location {
+            pointer,
+            keyword: Some(keyword),
+            depth: self.depth + 1,
+        }
+    }
+
+    fn target(&self, reference: &RefProperty) -> Location {
+        let pointer = match reference.reference.strip_prefix('#') {
+            Some(fragment) => fragment.to_owned(),
+            None => reference.reference.clone(),
+        };
+        Location {
+            pointer,
+            keyword: Some("$ref"),
+            depth: self.depth + 1,
+        }
+    }
+}
+
+/// Looks at every subschema of a document, see [`Schema::walk`]
+pub trait Visitor {
+    /// Called for each subschema before the ones below it
+    fn visit(&mut self, subschema: &Property, location: &Location);
+}
+
+impl<F> Visitor for F
+where
+    F: FnMut(&Property, &Location),
+{
+    fn visit(&mut self, subschema: &Property, location: &Location) {
+        self(subschema, location)
+    }
+}
+
+/// Edits every subschema of a document, see [`Schema::walk_mut`]
+pub trait VisitorMut {
+    /// Called for each subschema before the ones below it, which are only looked up afterwards.
+    fn visit_mut(&mut self, subschema: &mut Property, location: &Location);
+}
+
+impl<F> VisitorMut for F
+where
+    F: FnMut(&mut Property, &Location),
+{
+    fn visit_mut(&mut self, subschema: &mut Property, location: &Location) {
+        self(subschema, location)
+    }
+}
+
+/// Whether [`Schema::walk_with`] continues at the target of a `$ref`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Refs {
+    /// Only visit the subschema with the `$ref`
+    Ignore,
+    /// Also visit the subschema it points at, unless the reference is already being followed
+    Follow,
+}
+
+struct Walker<'s> {
+    root: &'s Schema,
+    refs: Refs,
+    following: Vec<&'s str>,
+}
+
+impl<'s> Walker<'s> {
+    fn definition<V: Visitor + ?Sized>(
+        &mut self,
+        definition: &'s SchemaDefinition,
+        location: &Location,
+        visitor: &mut V,
+    ) {
+        if let Some(specification) = &definition.specification {
+            self.property(specification, location, visitor);
+        }
+        for (keyword, definitions) in [
+            ("definitions", &definition.definitions),
+            ("$defs", &definition.defs),
+        ] {
+            for (name, definition) in definitions.iter().flat_map(SchemaMap::iter) {
+                self.definition(definition, &location.child(keyword, Some(name)), visitor);
+            }
+        }
+    }
+
+    fn property<V: Visitor + ?Sized>(
+        &mut self,
+        property: &'s Property,
+        location: &Location,
+        visitor: &mut V,
+    ) {
+        visitor.visit(property, location);
+        for (keyword, key, subschema) in property.subschemas() {
+            self.property(subschema, &location.child(keyword, key.as_deref()), visitor);
+        }
+        match property {
+            Property::Ref(reference) if self.refs == Refs::Follow => {
+                if self.following.contains(&reference.reference.as_str()) {
+                    return;
+                }
+                match reference.resolve(self.root) {
+                    Ok(target) => {
+                        self.following.push(&reference.reference);
+                        self.property(target, &location.target(reference), visitor);
+                        self.following.pop();
+                    }
+                    Err(e) => log::debug!("not following {:?}: {}", reference.reference, e),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn definition_mut<V: VisitorMut + ?Sized>(
+    definition: &mut SchemaDefinition,
+    location: &Location,
+    visitor: &mut V,
+) {
+    if let Some(specification) = &mut definition.specification {
+        property_mut(specification, location, visitor);
+    }
+    for (keyword, definitions) in [
+        ("definitions", &mut definition.definitions),
+        ("$defs", &mut definition.defs),
+    ] {
+        for (name, definition) in definitions.iter_mut().flat_map(SchemaMap::iter_mut) {
+            definition_mut(definition, &location.child(keyword, Some(name)), visitor);
+        }
+    }
+}
+
+fn property_mut<V: VisitorMut + ?Sized>(
+    property: &mut Property,
+    location: &Location,
+    visitor: &mut V,
+) {
+    visitor.visit_mut(property, location);
+    for (keyword, key, subschema) in property.subschemas_mut() {
+        property_mut(subschema, &location.child(keyword, key.as_deref()), visitor);
+    }
+}
+
+impl Schema {
+    /// Shows every subschema of this document to `visitor`, including definitions, parents first
+    pub fn walk<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        self.walk_with(visitor, Refs::Ignore)
+    }
+
+    /// Like [`Schema::walk`], but decides whether the targets of `$ref`s are visited as well
+    pub fn walk_with<V: Visitor + ?Sized>(&self, visitor: &mut V, refs: Refs) {
+        let mut walker = Walker {
+            root: self,
+            refs,
+            following: Vec::new(),
+        };
+        match (&self.0, self.as_property()) {
+            (SchemaInner::Schema(definition), _) => {
+                walker.definition(definition, &Location::root(), visitor)
+            }
+            (SchemaInner::Boolean(_), Some(property)) => {
+                walker.property(property, &Location::root(), visitor)
+            }
+            (SchemaInner::Boolean(_), None) => unreachable!("booleans are subschemas"),
+        }
+    }
+
+    /// Lets `visitor` edit every subschema of this document, including definitions, parents first.
+    ///
+    /// Unlike [`Schema::walk_with`], this can't follow `$ref`s: a target is only visited where it
+    /// is defined, so a subschema referenced several times is edited once, and targets in other
+    /// documents are not visited at all. To edit what a `$ref` points at, find its location with
+    /// [`Refs::Follow`] and replace it with [`Schema::insert`], or inline the references with
+    /// [`Schema::dereferenced`] first.
+    pub fn walk_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        if let SchemaInner::Boolean(value) = self.0 {
+            // only turn the document into a definition if the visitor changed it
+            let mut property = Property::Boolean(value);
+            property_mut(&mut property, &Location::root(), visitor);
+            if property != Property::Boolean(value) {
+                *self.as_property_mut() = property;
+            }
+        } else if let SchemaInner::Schema(definition) = &mut self.0 {
+            definition_mut(definition, &Location::root(), visitor);
+        }
+    }
+}
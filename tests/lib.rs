@@ -87,6 +87,8 @@ mod numbers {
 
         schema.validate(&json!({ "integer": -42 })).unwrap();
         schema.validate(&json!({ "integer": 42 })).unwrap();
+        schema.validate(&json!({ "integer": 42.0 })).unwrap();
+        schema.validate(&json!({ "integer": 4.2 })).unwrap_err();
         schema.validate(&json!({ "number": -4.1416 })).unwrap();
         schema.validate(&json!({ "number": 4.1416 })).unwrap();
     }
@@ -384,3 +386,3127 @@ mod validation {
         schema.validate(&json_missing).unwrap();
     }
 }
+
+mod references {
+    use serde_json::json;
+    use serde_json_schema::{error::ErrorKind, property::Property, *};
+
+    #[test]
+    fn self_reference_is_a_cycle() {
+        let schema = Schema::try_from(r##"{ "$ref": "#" }"##).unwrap();
+        assert!(schema.validate(&json!(42)).is_err());
+    }
+
+    #[test]
+    fn mutual_references_are_a_cycle() {
+        let schema = Schema::try_from(
+            r##"{
+            "type": "object",
+            "properties": {
+                "a": { "$ref": "#/properties/b" },
+                "b": { "$ref": "#/properties/a" }
+            }
+        }"##,
+        )
+        .unwrap();
+
+        let Some(Property::Ref(a)) = schema.properties().unwrap().get("a") else {
+            panic!("expected a reference");
+        };
+        let error = a.resolve(&schema).unwrap_err();
+        match error.kind() {
+            ErrorKind::RefCycle(chain) => assert_eq!(
+                chain,
+                &["#/properties/b", "#/properties/a", "#/properties/b"]
+            ),
+            other => panic!("unexpected error {:?}", other),
+        }
+        assert!(a.deref(&schema).is_none());
+        assert!(schema.validate(&json!({ "a": 1 })).is_err());
+    }
+
+    #[test]
+    fn chained_reference() {
+        let schema = Schema::try_from(
+            r##"{
+            "type": "object",
+            "properties": {
+                "a": { "$ref": "#/properties/b" },
+                "b": { "$ref": "#/properties/c" },
+                "c": { "type": "integer" }
+            }
+        }"##,
+        )
+        .unwrap();
+
+        schema.validate(&json!({ "a": 1 })).unwrap();
+        assert!(schema.validate(&json!({ "a": "one" })).is_err());
+    }
+
+    #[test]
+    fn linked_list() {
+        let schema = Schema::try_from(
+            r##"{
+            "definitions": {
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "value": { "type": "integer" },
+                        "next": { "$ref": "#/definitions/node" }
+                    },
+                    "required": [ "value" ]
+                }
+            },
+            "$ref": "#/definitions/node"
+        }"##,
+        )
+        .unwrap();
+
+        schema
+            .validate(&json!({ "value": 1, "next": { "value": 2, "next": { "value": 3 } } }))
+            .unwrap();
+        assert!(schema
+            .validate(&json!({ "value": 1, "next": { "value": 2, "next": { "value": "3" } } }))
+            .is_err());
+    }
+
+    #[test]
+    fn tree() {
+        let schema = Schema::try_from(
+            r##"{
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "children": {
+                    "type": "array",
+                    "items": { "$ref": "#" }
+                }
+            },
+            "required": [ "name" ]
+        }"##,
+        )
+        .unwrap();
+
+        schema
+            .validate(&json!({
+                "name": "root",
+                "children": [
+                    { "name": "leaf" },
+                    { "name": "branch", "children": [ { "name": "leaf" } ] }
+                ]
+            }))
+            .unwrap();
+        assert!(schema
+            .validate(&json!({ "name": "root", "children": [ { "children": [] } ] }))
+            .is_err());
+    }
+
+    #[test]
+    fn unresolvable_reference() {
+        let raw = include_str!("./fixtures/calendar.schema.json");
+        let schema = Schema::try_from(raw).unwrap();
+
+        let Some(Property::Ref(geo)) = schema.properties().unwrap().get("geo") else {
+            panic!("expected a reference");
+        };
+        assert!(matches!(
+            geo.resolve(&schema).unwrap_err().kind(),
+            ErrorKind::UnresolvableRef(_)
+        ));
+    }
+}
+
+mod bundling {
+    use serde_json::json;
+    use serde_json_schema::{
+        error::{ErrorKind, Result},
+        property::Property,
+        *,
+    };
+    use std::collections::HashMap;
+
+    fn fixtures() -> HashMap<Url, Schema> {
+        let mut resolver = HashMap::new();
+        resolver.insert(
+            Url::parse("http://example.com/address.schema.json").unwrap(),
+            Schema::try_from(include_str!("./fixtures/address.schema.json")).unwrap(),
+        );
+        resolver.insert(
+            Url::parse("http://example.com/geographical-location.schema.json").unwrap(),
+            Schema::try_from(include_str!("./fixtures/geographical-location.schema.json")).unwrap(),
+        );
+        resolver
+    }
+
+    #[test]
+    fn inlines_for_draft_07() {
+        let schema = Schema::try_from(include_str!("./fixtures/card.schema.json")).unwrap();
+        let bundled = schema.bundle(&fixtures()).unwrap();
+
+        let Some(Property::Ref(adr)) = bundled.properties().unwrap().get("adr") else {
+            panic!("expected a reference");
+        };
+        assert_eq!(adr.reference, "#/definitions/address.schema.json");
+        assert!(adr.deref(&bundled).is_some());
+
+        let reparsed = Schema::try_from(serde_json::to_value(&bundled).unwrap()).unwrap();
+        assert_eq!(bundled, reparsed);
+
+        let card = json!({
+            "familyName": "Doe",
+            "givenName": "Jane",
+            "adr": { "locality": "Berlin", "region": "Berlin", "country-name": "Germany" },
+            "geo": { "latitude": 52.5, "longitude": 13.4 }
+        });
+        bundled.validate(&card).unwrap();
+
+        let wrong_geo = json!({
+            "familyName": "Doe",
+            "givenName": "Jane",
+            "geo": { "latitude": "north", "longitude": 13.4 }
+        });
+        assert!(bundled.validate(&wrong_geo).is_err());
+    }
+
+    #[test]
+    fn embeds_under_defs_with_ids() {
+        let schema = Schema::try_from(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/order.json",
+            "type": "object",
+            "properties": {
+                "id": { "$ref": "common.json#/properties/id" },
+                "customer": { "$ref": "customer.json" }
+            }
+        }))
+        .unwrap();
+
+        let resolver = |url: &Url| -> Result<Schema> {
+            match url.as_str() {
+                "https://example.com/common.json" => Schema::try_from(json!({
+                    "type": "object",
+                    "properties": { "id": { "type": "integer" } }
+                })),
+                "https://example.com/customer.json" => Schema::try_from(json!({
+                    "$id": "https://example.com/customer.json",
+                    "type": "object",
+                    "properties": {
+                        "id": { "$ref": "common.json#/properties/id" },
+                        "name": { "type": "string" }
+                    },
+                    "required": [ "id" ]
+                })),
+                _ => Err(ErrorKind::UnresolvableRef(url.to_string()).into()),
+            }
+        };
+
+        let bundled = schema.bundle(&resolver).unwrap();
+        let raw = serde_json::to_value(&bundled).unwrap();
+        assert_eq!(
+            raw["$defs"]["common.json"]["$id"],
+            json!("https://example.com/common.json")
+        );
+        assert_eq!(
+            raw["$defs"]["customer.json"]["properties"]["id"]["$ref"],
+            json!("https://example.com/common.json#/properties/id")
+        );
+        assert_eq!(
+            raw["properties"]["customer"]["$ref"],
+            json!("https://example.com/customer.json")
+        );
+
+        bundled
+            .validate(&json!({ "id": 1, "customer": { "id": 2, "name": "Jane" } }))
+            .unwrap();
+        assert!(bundled
+            .validate(&json!({ "id": 1, "customer": { "id": "two" } }))
+            .is_err());
+    }
+
+    #[test]
+    fn without_schema_like_the_latest_draft() {
+        let schema = Schema::try_from(json!({
+            "$id": "http://example.com/person.json",
+            "type": "object",
+            "properties": { "address": { "$ref": "address.schema.json" } }
+        }))
+        .unwrap();
+
+        let bundled = schema.bundle(&fixtures()).unwrap();
+        let raw = serde_json::to_value(&bundled).unwrap();
+        assert_eq!(
+            raw["$defs"]["address.schema.json"]["$id"],
+            json!("https://example.com/address.schema.json")
+        );
+        assert!(raw.get("definitions").is_none());
+    }
+
+    #[test]
+    fn missing_document() {
+        let schema = Schema::try_from(include_str!("./fixtures/calendar.schema.json")).unwrap();
+        assert!(schema.bundle(&fixtures()).is_err());
+    }
+}
+
+mod dereferencing {
+    use serde_json::json;
+    use serde_json_schema::{
+        error::ErrorKind,
+        property::{Property, RecursiveRefs},
+        *,
+    };
+
+    #[test]
+    fn inlines_references() {
+        let schema = Schema::try_from(json!({
+            "definitions": {
+                "id": { "type": "integer" },
+                "name": { "$ref": "#/definitions/string" },
+                "string": { "type": "string" }
+            },
+            "type": "object",
+            "properties": {
+                "id": { "$ref": "#/definitions/id" },
+                "names": { "type": "array", "items": { "$ref": "#/definitions/name" } }
+            }
+        }))
+        .unwrap();
+
+        let dereferenced = schema.dereferenced().unwrap();
+        let raw = serde_json::to_value(&dereferenced).unwrap();
+        assert_eq!(raw["properties"]["id"], json!({ "type": "integer" }));
+        assert_eq!(
+            raw["properties"]["names"]["items"],
+            json!({ "type": "string" })
+        );
+        assert_eq!(raw["definitions"]["name"], json!({ "type": "string" }));
+    }
+
+    #[test]
+    fn keeps_recursive_references() {
+        let schema = Schema::try_from(json!({
+            "definitions": {
+                "node": {
+                    "type": "object",
+                    "properties": {
+                        "value": { "$ref": "#/definitions/value" },
+                        "next": { "$ref": "#/definitions/node" }
+                    }
+                },
+                "value": { "type": "integer" }
+            },
+            "$ref": "#/definitions/node"
+        }))
+        .unwrap();
+
+        let dereferenced = schema.dereferenced().unwrap();
+        assert!(matches!(
+            dereferenced.properties().unwrap().get("value"),
+            Some(Property::Value(_))
+        ));
+        let Some(Property::Ref(next)) = dereferenced.properties().unwrap().get("next") else {
+            panic!("expected the recursive reference to be kept");
+        };
+        assert!(next.deref(&dereferenced).is_some());
+        dereferenced
+            .validate(&json!({ "value": 1, "next": { "value": 2 } }))
+            .unwrap();
+
+        let error = schema.dereferenced_with(RecursiveRefs::Error).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::RecursiveRef(r) if r == "#/definitions/node"));
+    }
+
+    #[test]
+    fn keeps_keywords_next_to_references() {
+        let raw = json!({
+            "$defs": {
+                "number": { "type": "number" },
+                "integer": { "type": "integer" }
+            },
+            "type": "object",
+            "properties": {
+                "id": {
+                    "$ref": "#/$defs/number",
+                    "minimum": 1,
+                    "allOf": [{ "$ref": "#/$defs/integer" }]
+                }
+            }
+        });
+        let schema = Schema::try_from(raw.clone()).unwrap();
+
+        let dereferenced = schema.dereferenced().unwrap();
+        assert_eq!(
+            serde_json::to_value(&dereferenced).unwrap()["properties"]["id"],
+            json!({
+                "allOf": [
+                    { "type": "number" },
+                    { "minimum": 1, "allOf": [{ "type": "integer" }] }
+                ]
+            })
+        );
+        for instance in [json!({ "id": 2 }), json!({ "id": 1.5 })] {
+            assert_eq!(
+                schema.validate(&instance).is_ok(),
+                dereferenced.validate(&instance).is_ok()
+            );
+        }
+        assert!(dereferenced.validate(&json!({ "id": 1.5 })).is_err());
+
+        // before 2019-09 the keywords next to `$ref` are ignored
+        let mut draft7 = raw;
+        draft7["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+        let dereferenced = Schema::try_from(draft7).unwrap().dereferenced().unwrap();
+        assert_eq!(
+            serde_json::to_value(&dereferenced).unwrap()["properties"]["id"],
+            json!({ "type": "number" })
+        );
+    }
+
+    #[test]
+    fn unresolvable_references() {
+        let schema = Schema::try_from(include_str!("./fixtures/calendar.schema.json")).unwrap();
+        assert!(schema.dereferenced().is_err());
+    }
+}
+
+mod extra_keywords {
+    use serde_json::json;
+    use serde_json_schema::{property::Property, *};
+
+    #[test]
+    fn preserved_on_every_level() {
+        let raw = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "Product",
+            "x-owner": "catalog-team",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "x-db-column": "product_id", "$comment": "primary key" },
+                "tags": { "type": "array", "items": { "type": "string", "examples": ["home"] } },
+                "anything": { "x-opaque": true },
+                "parent": { "$ref": "#", "x-lazy": true }
+            },
+            "definitions": {
+                "unused": { "type": "null", "x-deprecated-since": "1.2" }
+            }
+        });
+
+        let schema = Schema::try_from(raw.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), raw);
+    }
+
+    #[test]
+    fn queryable_by_name() {
+        let schema = Schema::try_from(json!({
+            "title": "Product",
+            "x-owner": "catalog-team",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "x-db-column": "product_id" },
+                "anything": { "x-opaque": true },
+                "parent": { "$ref": "#", "x-lazy": true }
+            }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            schema.extra_keyword("x-owner"),
+            Some(&json!("catalog-team"))
+        );
+        assert_eq!(schema.extra_keyword("title"), None);
+        assert_eq!(schema.extra_keyword("type"), None);
+
+        let properties = schema.properties().unwrap();
+        assert_eq!(
+            properties["id"].extra_keyword("x-db-column"),
+            Some(&json!("product_id"))
+        );
+        assert!(matches!(properties["anything"], Property::Any(_)));
+        assert_eq!(
+            properties["anything"].extra_keyword("x-opaque"),
+            Some(&json!(true))
+        );
+        assert_eq!(
+            properties["parent"].extra_keyword("x-lazy"),
+            Some(&json!(true))
+        );
+    }
+}
+
+mod serialization {
+    use serde_json::Value;
+    use serde_json_schema::*;
+    use std::fs;
+
+    #[test]
+    fn fixtures_round_trip() {
+        for entry in fs::read_dir("./tests/fixtures").unwrap() {
+            let path = entry.unwrap().path();
+            if !path.to_string_lossy().ends_with(".schema.json") {
+                continue;
+            }
+            let raw = fs::read_to_string(&path).unwrap();
+            let schema = Schema::try_from(raw.as_str()).unwrap();
+            let serialized = serde_json::to_string(&schema).unwrap();
+
+            let original: Value = serde_json::from_str(&raw).unwrap();
+            let round_tripped: Value = serde_json::from_str(&serialized).unwrap();
+            assert_eq!(original, round_tripped, "{} changed", path.display());
+            assert_eq!(schema, Schema::try_from(serialized).unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "preserve_order")]
+    fn properties_keep_their_order() {
+        let raw = include_str!("./fixtures/card.schema.json");
+        let schema = Schema::try_from(raw).unwrap();
+
+        let keys = |value: &Value| -> Vec<String> {
+            value["properties"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect()
+        };
+        let original: Value = serde_json::from_str(raw).unwrap();
+        let serialized = serde_json::to_value(&schema).unwrap();
+        assert_eq!(keys(&original), keys(&serialized));
+        assert_eq!(
+            schema.properties().unwrap().keys().collect::<Vec<_>>(),
+            keys(&original).iter().collect::<Vec<_>>()
+        );
+    }
+}
+
+mod annotations {
+    use serde_json::json;
+    use serde_json_schema::*;
+
+    #[test]
+    fn on_the_root() {
+        let schema = Schema::try_from(include_str!("./fixtures/green_door.schema.json")).unwrap();
+        assert_eq!(schema.title(), Some("Product"));
+        assert_eq!(schema.description(), Some("A product from Acme's catalog"));
+        assert!(!schema.deprecated());
+        assert!(schema.examples().is_empty());
+    }
+
+    #[test]
+    fn on_every_subschema() {
+        let raw = json!({
+            "type": "object",
+            "properties": {
+                "id": {
+                    "type": "integer",
+                    "title": "Identifier",
+                    "description": "The unique identifier for a product",
+                    "readOnly": true,
+                    "$comment": "assigned by the database"
+                },
+                "password": { "type": "string", "writeOnly": true },
+                "legacyCode": { "type": "string", "deprecated": true, "default": null },
+                "color": { "description": "any color will do", "examples": ["green", "#00ff00"] },
+                "parent": { "$ref": "#", "description": "the parent product" }
+            }
+        });
+        let schema = Schema::try_from(raw.clone()).unwrap();
+        let properties = schema.properties().unwrap();
+
+        let id = &properties["id"];
+        assert_eq!(id.title(), Some("Identifier"));
+        assert_eq!(
+            id.description(),
+            Some("The unique identifier for a product")
+        );
+        assert_eq!(id.comment(), Some("assigned by the database"));
+        assert!(id.read_only());
+        assert!(!id.write_only());
+
+        assert!(properties["password"].write_only());
+
+        let legacy = &properties["legacyCode"];
+        assert!(legacy.deprecated());
+        assert_eq!(legacy.default(), Some(&json!(null)));
+        assert!(legacy.extra().is_empty());
+
+        assert_eq!(properties["color"].description(), Some("any color will do"));
+        assert_eq!(
+            properties["color"].examples(),
+            &[json!("green"), json!("#00ff00")]
+        );
+        assert_eq!(
+            properties["parent"].description(),
+            Some("the parent product")
+        );
+
+        assert_eq!(serde_json::to_value(&schema).unwrap(), raw);
+    }
+
+    #[test]
+    fn collected_during_validation() {
+        let schema = Schema::try_from(json!({
+            "title": "Product",
+            "type": "object",
+            "properties": {
+                "id": { "type": "integer", "readOnly": true },
+                "legacyCode": { "type": "string", "deprecated": true },
+                "tags": {
+                    "type": "array",
+                    "items": { "$ref": "#/definitions/tag", "description": "a single tag" }
+                }
+            },
+            "definitions": {
+                "tag": { "type": "string", "title": "Tag" }
+            }
+        }))
+        .unwrap();
+
+        let annotations = schema
+            .annotate(&json!({ "id": 1, "legacyCode": "A-1", "tags": ["home"] }))
+            .unwrap();
+
+        assert_eq!(annotations[""].title(), Some("Product"));
+        assert_eq!(
+            annotations[""].evaluated_properties,
+            ["id", "legacyCode", "tags"]
+        );
+        assert!(annotations["/id"].read_only());
+        assert!(annotations["/legacyCode"].deprecated());
+        assert_eq!(annotations["/tags"].evaluated_items, [0]);
+        assert_eq!(annotations["/tags/0"].title(), Some("Tag"));
+        assert_eq!(annotations["/tags/0"].description(), Some("a single tag"));
+
+        let deprecated: Vec<_> = annotations
+            .iter()
+            .filter(|(_, location)| location.deprecated())
+            .map(|(pointer, _)| pointer.as_str())
+            .collect();
+        assert_eq!(deprecated, ["/legacyCode"]);
+
+        assert!(schema.annotate(&json!({ "id": "one" })).is_err());
+    }
+}
+
+mod unevaluated {
+    use serde_json::json;
+    use serde_json_schema::*;
+
+    #[test]
+    fn properties_of_all_of_branches() {
+        let schema = Schema::try_from(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "allOf": [
+                { "properties": { "name": { "type": "string" } } },
+                { "properties": { "age": { "type": "integer" } } }
+            ],
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"name": "Ada", "age": 36})).is_ok());
+        assert_eq!(
+            schema.validate(&json!({"name": "Ada", "email": "ada@example.com"})),
+            Err(vec![String::from(
+                "unevaluated property \"email\" is not allowed"
+            )])
+        );
+    }
+
+    #[test]
+    fn properties_of_untyped_branches() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "allOf": [{ "properties": { "a": { "type": "integer" } } }],
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"a": 1})).is_ok());
+        assert!(schema.validate(&json!({"a": "one"})).is_err());
+        assert!(schema.validate(&json!({"a": 1, "b": 2})).is_err());
+    }
+
+    #[test]
+    fn properties_next_to_references() {
+        let schema = Schema::try_from(json!({
+            "$defs": { "named": { "properties": { "name": { "type": "string" } } } },
+            "$ref": "#/$defs/named",
+            "properties": { "age": { "type": "integer" } },
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"name": "Ada", "age": 36})).is_ok());
+        assert!(schema
+            .validate(&json!({"name": "Ada", "age": "old"}))
+            .is_err());
+        assert!(schema
+            .validate(&json!({"name": "Ada", "email": "ada@example.com"}))
+            .is_err());
+    }
+
+    #[test]
+    fn properties_of_conditionals() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "properties": { "kind": { "type": "string" } },
+            "if": { "required": ["wheels"] },
+            "then": { "properties": { "wheels": { "type": "integer" } } },
+            "else": { "properties": { "wings": { "type": "integer" } } },
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema
+            .validate(&json!({"kind": "car", "wheels": 4}))
+            .is_ok());
+        assert!(schema
+            .validate(&json!({"kind": "plane", "wings": 2}))
+            .is_ok());
+        assert!(schema.validate(&json!({"wheels": "four"})).is_err());
+        assert!(schema.validate(&json!({"wheels": 4, "wings": 2})).is_err());
+    }
+
+    #[test]
+    fn properties_of_any_of_and_one_of() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "anyOf": [
+                { "properties": { "a": { "type": "integer" } }, "required": ["a"] },
+                { "properties": { "b": { "type": "integer" } }, "required": ["b"] }
+            ],
+            "oneOf": [
+                { "properties": { "c": { "type": "integer" } }, "required": ["c"] },
+                { "properties": { "d": { "type": "integer" } }, "required": ["d"] }
+            ],
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"a": 1, "b": 2, "c": 3})).is_ok());
+        assert!(schema.validate(&json!({"a": 1, "c": 3, "d": 4})).is_err());
+        assert!(schema.validate(&json!({"a": 1, "d": 4, "e": 5})).is_err());
+    }
+
+    #[test]
+    fn properties_behind_references() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "$defs": {
+                "named": { "properties": { "name": { "type": "string" } } }
+            },
+            "allOf": [{ "$ref": "#/$defs/named" }],
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"name": "Ada"})).is_ok());
+        assert!(schema.validate(&json!({"name": "Ada", "age": 36})).is_err());
+    }
+
+    #[test]
+    fn pattern_and_additional_properties() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "properties": { "id": { "type": "integer" } },
+            "patternProperties": { "^x-": { "type": "string" } },
+            "additionalProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"id": 1, "x-note": "hi"})).is_ok());
+        assert!(schema.validate(&json!({"x-note": 1})).is_err());
+        assert_eq!(
+            schema.validate(&json!({"id": 1, "name": "Ada"})),
+            Err(vec![String::from(
+                "additional property \"name\" is not allowed"
+            )])
+        );
+    }
+
+    #[test]
+    fn not_evaluates_nothing() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "not": { "properties": { "a": { "type": "string" } }, "required": ["a"] },
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({})).is_ok());
+        assert!(schema.validate(&json!({"a": 1})).is_err());
+    }
+
+    #[test]
+    fn items() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "evaluated": { "type": "array", "items": { "type": "integer" }, "unevaluatedItems": false },
+                "closed": { "unevaluatedItems": false }
+            }
+        }))
+        .unwrap();
+
+        assert!(schema.validate(&json!({"evaluated": [1, 2, 3]})).is_ok());
+        assert!(schema.validate(&json!({"evaluated": [1, "two"]})).is_err());
+        assert!(schema.validate(&json!({"closed": []})).is_ok());
+        assert_eq!(
+            schema.validate(&json!({"closed": [1]})),
+            Err(vec![String::from("unevaluated item 0 is not allowed")])
+        );
+    }
+}
+
+mod formats {
+    use serde_json::json;
+    use serde_json_schema::{format, property::Property, *};
+
+    fn schema() -> Schema {
+        Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "created": { "type": "string", "format": "date-time" },
+                "contact": { "type": "string", "format": "email" },
+                "sku": { "type": "string", "format": "sku" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn parsed() {
+        let schema = schema();
+        match &schema.properties().unwrap()["created"] {
+            Property::Value(created) => assert_eq!(created.format(), Some("date-time")),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn annotation_only_by_default() {
+        assert!(schema().validate(&json!({"created": "yesterday"})).is_ok());
+    }
+
+    #[test]
+    fn asserted_when_enabled() {
+        let options = ValidationOptions::new().assert_formats(true);
+        let schema = schema();
+        assert!(schema
+            .validate_with(
+                &json!({"created": "1985-04-12T23:20:50.52Z", "contact": "ada@example.com"}),
+                &options
+            )
+            .is_ok());
+        assert_eq!(
+            schema.validate_with(&json!({"created": "yesterday"}), &options),
+            Err(vec![String::from(
+                r#""yesterday" is not a valid "date-time""#
+            )])
+        );
+        // unknown formats are ignored
+        assert!(schema
+            .validate_with(&json!({"sku": "anything"}), &options)
+            .is_ok());
+    }
+
+    #[test]
+    fn without_a_single_type() {
+        let options = ValidationOptions::new().assert_formats(true);
+        for schema in [
+            json!({"format": "email"}),
+            json!({"type": ["string", "null"], "format": "email"}),
+            json!({"$defs": {"s": {"minLength": 1}}, "$ref": "#/$defs/s", "format": "email"}),
+        ] {
+            let schema = Schema::try_from(schema).unwrap();
+            assert_eq!(
+                schema.validate_with(&json!("2962"), &options),
+                Err(vec![String::from(r#""2962" is not a valid "email""#)])
+            );
+            assert!(schema
+                .validate_with(&json!("ada@example.com"), &options)
+                .is_ok());
+            // other types are not strings
+            assert!(schema.validate_with(&json!(null), &options).is_ok());
+        }
+        let untyped = Schema::any().format("email").build();
+        assert!(untyped.validate_with(&json!("2962"), &options).is_err());
+    }
+
+    #[test]
+    fn checkers() {
+        let cases = [
+            ("date-time", "1990-12-31T15:59:60-08:00", true),
+            ("date-time", "1990-02-31T15:59:59Z", false),
+            ("date-time", "1990-12-31 15:59:59Z", false),
+            ("date", "2020-02-29", true),
+            ("date", "2019-02-29", false),
+            ("time", "08:30:06.283185Z", true),
+            ("time", "08:30:06", false),
+            ("duration", "P4DT12H30M5S", true),
+            ("duration", "P2W", true),
+            ("duration", "PT", false),
+            ("duration", "P1D2H", false),
+            ("email", "joe.bloggs@example.com", true),
+            ("email", "joe..bloggs@example.com", false),
+            ("hostname", "www.example.com", true),
+            ("hostname", "-example.com", false),
+            ("ipv4", "192.168.0.1", true),
+            ("ipv4", "256.256.256.256", false),
+            ("ipv6", "::1", true),
+            ("ipv6", "12345::", false),
+            ("uri", "http://foo.bar/?baz=qux#quux", true),
+            ("uri", "//foo.bar/?baz=qux#quux", false),
+            ("uri-reference", "/abc", true),
+            ("uri-reference", "\\\\WINDOWS\\fileshare", false),
+            ("uuid", "2eb8aa08-aa98-11ea-b4aa-73b441d16380", true),
+            ("uuid", "2eb8aa08-aa98-11ea-b4aa73b441d16380", false),
+            ("json-pointer", "/foo/bar~0/baz~1/%a", true),
+            ("json-pointer", "#/foo", false),
+            ("json-pointer", "/foo/bar~", false),
+            ("regex", "([abc])+\\s+$", true),
+            ("regex", "^(abc]", false),
+        ];
+        for (name, value, valid) in cases.iter() {
+            assert_eq!(
+                format::check(name, value),
+                Some(*valid),
+                "{} {:?}",
+                name,
+                value
+            );
+        }
+        assert_eq!(format::check("iban", "DE02"), None);
+    }
+
+    #[test]
+    fn custom() {
+        let options = ValidationOptions::new()
+            .assert_formats(true)
+            .format("sku", |value| value.starts_with("SKU-"))
+            .format("email", |value| value.ends_with("@example.com"));
+        let schema = schema();
+        assert!(schema
+            .validate_with(
+                &json!({"sku": "SKU-42", "contact": "ada@example.com"}),
+                &options
+            )
+            .is_ok());
+        assert_eq!(
+            schema.validate_with(&json!({"sku": "42"}), &options),
+            Err(vec![String::from(r#""42" is not a valid "sku""#)])
+        );
+        assert_eq!(
+            schema.validate_with(&json!({"contact": "ada@example.org"}), &options),
+            Err(vec![String::from(
+                r#""ada@example.org" is not a valid "email""#
+            )])
+        );
+    }
+
+    #[test]
+    fn strict() {
+        let schema = schema();
+        let options = ValidationOptions::new()
+            .assert_formats(true)
+            .strict_formats(true);
+        assert_eq!(
+            schema.validate_with(&json!({"sku": "SKU-42"}), &options),
+            Err(vec![String::from(r#"unknown format "sku""#)])
+        );
+        assert!(schema
+            .validate_with(
+                &json!({"sku": "SKU-42"}),
+                &options.clone().format("sku", |_| true)
+            )
+            .is_ok());
+
+        for untyped in [
+            json!({"format": "sku"}),
+            json!({"type": ["string", "null"], "format": "sku"}),
+        ] {
+            let untyped = Schema::try_from(untyped).unwrap();
+            assert_eq!(
+                untyped.validate_with(&json!("SKU-42"), &options),
+                Err(vec![String::from(r#"unknown format "sku""#)])
+            );
+            assert!(untyped.validate_with(&json!(null), &options).is_ok());
+        }
+    }
+}
+
+mod keywords {
+    use serde_json::{json, Value};
+    use serde_json_schema::{
+        error::{Error, Result},
+        keyword::Keyword,
+        *,
+    };
+    use std::collections::HashSet;
+
+    /// `"x-unique-by": "id"` requires the `id` of every element to be different
+    struct UniqueBy(String);
+
+    impl Keyword for UniqueBy {
+        fn compile(value: &Value) -> Result<Self> {
+            value
+                .as_str()
+                .map(|field| UniqueBy(field.to_owned()))
+                .ok_or_else(|| Error::keyword("expected the name of a field"))
+        }
+
+        fn validate(&self, instance: &Value) -> Result<()> {
+            let mut seen = HashSet::new();
+            for element in instance.as_array().into_iter().flatten() {
+                let key = element.get(&self.0).map(Value::to_string);
+                if !seen.insert(key.clone()) {
+                    return Err(Error::keyword(format!(
+                        "{:?} is not unique by {:?}",
+                        key.unwrap_or_default(),
+                        self.0
+                    )));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn schema(unique_by: Value) -> Schema {
+        Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "users": {
+                    "type": "array",
+                    "items": { "type": "object" },
+                    "x-unique-by": unique_by
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn ignored_unless_registered() {
+        let users = json!({"users": [{"id": 1}, {"id": 1}]});
+        assert!(schema(json!("id")).validate(&users).is_ok());
+    }
+
+    #[test]
+    fn validated_when_registered() {
+        let options = ValidationOptions::new().keyword::<UniqueBy>("x-unique-by");
+        let schema = schema(json!("id"));
+        assert!(schema
+            .validate_with(&json!({"users": [{"id": 1}, {"id": 2}]}), &options)
+            .is_ok());
+        assert_eq!(
+            schema.validate_with(&json!({"users": [{"id": 1}, {"id": 1}]}), &options),
+            Err(vec![String::from(r#""1" is not unique by "id""#)])
+        );
+    }
+
+    #[test]
+    fn invalid_keyword_value() {
+        let options = ValidationOptions::new().keyword::<UniqueBy>("x-unique-by");
+        assert_eq!(
+            schema(json!(42)).validate_with(&json!({"users": []}), &options),
+            Err(vec![String::from(
+                r#"invalid value for keyword "x-unique-by": expected the name of a field"#
+            )])
+        );
+    }
+}
+
+mod content {
+    use serde_json::json;
+    use serde_json_schema::*;
+
+    fn schema() -> Schema {
+        Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "payload": {
+                    "type": "string",
+                    "contentEncoding": "base64",
+                    "contentMediaType": "application/json",
+                    "contentSchema": {
+                        "type": "object",
+                        "properties": { "id": { "type": "integer" } },
+                        "required": ["id"]
+                    }
+                },
+                "note": { "type": "string", "contentEncoding": "quoted-printable" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn annotations() {
+        let schema = schema();
+        let payload = &schema.properties().unwrap()["payload"];
+        assert_eq!(payload.content_encoding(), Some("base64"));
+        assert_eq!(payload.content_media_type(), Some("application/json"));
+        assert!(payload.content_schema().is_some());
+
+        let annotations = schema.annotate(&json!({"payload": "e30="})).unwrap();
+        assert_eq!(
+            annotations["/payload"].content_media_type(),
+            Some("application/json")
+        );
+    }
+
+    #[test]
+    fn ignored_by_default() {
+        assert!(schema()
+            .validate(&json!({"payload": "not base64!"}))
+            .is_ok());
+    }
+
+    #[test]
+    fn asserted_when_enabled() {
+        let options = ValidationOptions::new().assert_content(true);
+        let schema = schema();
+
+        // {"id": 1}
+        assert!(schema
+            .validate_with(&json!({"payload": "eyJpZCI6IDF9"}), &options)
+            .is_ok());
+        assert!(schema
+            .validate_with(&json!({"note": "caf=C3=A9 =\r\nsoft"}), &options)
+            .is_ok());
+        assert!(schema
+            .validate_with(&json!({"note": "=ZZ"}), &options)
+            .is_err());
+        assert!(schema
+            .validate_with(&json!({"payload": "not base64!"}), &options)
+            .is_err());
+        // [1
+        assert!(schema
+            .validate_with(&json!({"payload": "WzE="}), &options)
+            .is_err());
+    }
+
+    #[test]
+    fn errors_within_content_are_located() {
+        let options = ValidationOptions::new().assert_content(true);
+        // {"id": "one"}
+        assert_eq!(
+            schema().validate_with(&json!({"payload": "eyJpZCI6ICJvbmUifQ"}), &options),
+            Err(vec![String::from(
+                r#"/payload/id: expected integer found String("one")"#
+            )])
+        );
+        // {}
+        assert_eq!(
+            schema().validate_with(&json!({"payload": "e30"}), &options),
+            Err(vec![String::from(
+                r#"/payload: object doesn't contain the required property "id""#
+            )])
+        );
+    }
+}
+
+mod options {
+    use serde_json::json;
+    use serde_json_schema::{draft::Draft, *};
+    use std::collections::HashMap;
+
+    #[test]
+    fn draft_override() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "$defs": { "named": { "type": "object", "required": ["name"] } },
+            "properties": {
+                "owner": { "$ref": "#/$defs/named", "allOf": [{ "type": "object", "required": ["id"] }] }
+            },
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+        let draft_07 = ValidationOptions::new().draft(Draft::Draft07);
+
+        // neither keywords next to `$ref` nor `unevaluatedProperties` exist in draft-07
+        let instance = json!({"owner": {"name": "Ada"}});
+        assert_eq!(
+            schema.validate(&instance),
+            Err(vec![String::from(
+                r#"object doesn't contain the required property "id""#
+            )])
+        );
+        assert!(schema.validate_with(&instance, &draft_07).is_ok());
+
+        let instance = json!({"owner": {"name": "Ada", "id": 1}, "extra": true});
+        assert_eq!(
+            schema.validate(&instance),
+            Err(vec![String::from(
+                r#"unevaluated property "extra" is not allowed"#
+            )])
+        );
+        assert!(schema.validate_with(&instance, &draft_07).is_ok());
+    }
+
+    #[test]
+    fn max_errors_and_fail_fast() {
+        let schema = Schema::try_from(json!({
+            "type": "array",
+            "items": { "type": "integer" }
+        }))
+        .unwrap();
+        let instance = json!(["a", "b", "c", "d"]);
+
+        assert_eq!(schema.validate(&instance).unwrap_err().len(), 4);
+        let options = ValidationOptions::new().max_errors(2);
+        assert_eq!(
+            schema.validate_with(&instance, &options).unwrap_err().len(),
+            2
+        );
+        let options = ValidationOptions::new().fail_fast(true);
+        assert_eq!(
+            schema.validate_with(&instance, &options),
+            Err(vec![String::from(r#"expected integer found String("a")"#)])
+        );
+    }
+
+    #[test]
+    fn fail_fast_ignores_failed_branches() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "anyOf": [{ "type": "string" }, { "type": "object" }],
+            "properties": { "id": { "type": "integer" } }
+        }))
+        .unwrap();
+        let options = ValidationOptions::new().fail_fast(true);
+        assert!(schema.validate_with(&json!({"id": 1}), &options).is_ok());
+        assert_eq!(
+            schema.validate_with(&json!({"id": "one"}), &options),
+            Err(vec![String::from(
+                r#"expected integer found String("one")"#
+            )])
+        );
+    }
+
+    #[test]
+    fn resolver() {
+        let schema = Schema::try_from(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/place.schema.json",
+            "type": "object",
+            "properties": {
+                "location": { "$ref": "geographical-location.schema.json" }
+            }
+        }))
+        .unwrap();
+        let mut resolver = HashMap::new();
+        resolver.insert(
+            Url::parse("https://example.com/geographical-location.schema.json").unwrap(),
+            Schema::try_from(include_str!("./fixtures/geographical-location.schema.json")).unwrap(),
+        );
+        let options = ValidationOptions::new().resolver(resolver);
+
+        let valid = json!({"location": {"latitude": 52.5, "longitude": 13.4}});
+        assert!(schema.validate(&valid).is_err());
+        assert!(schema.validate_with(&valid, &options).is_ok());
+        assert!(schema
+            .validate_with(&json!({"location": {"latitude": 52.5}}), &options)
+            .is_err());
+    }
+
+    #[test]
+    fn regex_engine() {
+        let schema = Schema::try_from(json!({
+            "type": "object",
+            "patternProperties": { "^(?!x-)": { "type": "integer" } }
+        }))
+        .unwrap();
+        let instance = json!({"x-note": "hi", "id": 1});
+
+        // lookaround is not supported by the default engine
+        assert!(schema.validate(&instance).is_err());
+        #[cfg(feature = "fancy-regex")]
+        {
+            let options = ValidationOptions::new().regex_engine(RegexEngine::FancyRegex);
+            assert!(schema.validate_with(&instance, &options).is_ok());
+            assert!(schema
+                .validate_with(&json!({"id": "one"}), &options)
+                .is_err());
+        }
+    }
+}
+
+mod boolean_subschemas {
+    use serde_json::{json, Value};
+    use serde_json_schema::{property::Property, *};
+
+    fn raw() -> Value {
+        json!({
+            "type": "object",
+            "definitions": { "never": false, "anything": true },
+            "properties": {
+                "legacy": false,
+                "tags": { "type": "array", "items": true },
+                "id": { "allOf": [true, { "type": "integer" }] },
+                "removed": { "$ref": "#/definitions/never" },
+                "free": { "$ref": "#/definitions/anything" }
+            },
+            "additionalProperties": false
+        })
+    }
+
+    #[test]
+    fn parsed_in_every_position() {
+        let schema = Schema::try_from(raw()).unwrap();
+        let properties = schema.properties().unwrap();
+        assert_eq!(properties["legacy"], Property::Boolean(false));
+        assert!(properties["legacy"].is_false());
+    }
+
+    #[test]
+    fn round_trip() {
+        let schema = Schema::try_from(raw()).unwrap();
+        assert_eq!(serde_json::to_value(&schema).unwrap(), raw());
+    }
+
+    #[test]
+    fn validated() {
+        let schema = Schema::try_from(raw()).unwrap();
+        assert!(schema
+            .validate(&json!({"tags": [1, "two", null], "id": 3, "free": {}}))
+            .is_ok());
+        assert!(schema.validate(&json!({"legacy": 1})).is_err());
+        assert!(schema.validate(&json!({"removed": 1})).is_err());
+        assert!(schema.validate(&json!({"id": "three"})).is_err());
+        assert_eq!(
+            schema.validate(&json!({"unknown": 1})),
+            Err(vec![String::from(
+                r#"additional property "unknown" is not allowed"#
+            )])
+        );
+    }
+
+    #[test]
+    fn at_the_root() {
+        let accept = Schema::try_from(json!(true)).unwrap();
+        let reject = Schema::try_from(json!(false)).unwrap();
+        assert!(accept.validate(&json!({"any": "thing"})).is_ok());
+        assert_eq!(
+            reject.validate(&json!(1)),
+            Err(vec![String::from(
+                r#"the schema "false" does not accept Number(1)"#
+            )])
+        );
+    }
+}
+
+mod root {
+    use serde_json::json;
+    use serde_json_schema::{property::Property, *};
+
+    #[test]
+    fn every_type() {
+        let kinds = [
+            (
+                "null",
+                Schema::as_null as fn(&Schema) -> Option<&property::PropertyInstance>,
+            ),
+            ("boolean", Schema::as_boolean),
+            ("integer", Schema::as_integer),
+            ("number", Schema::as_number),
+            ("string", Schema::as_string),
+            ("object", Schema::as_object),
+        ];
+        for (kind, as_kind) in kinds.iter() {
+            let schema = Schema::try_from(json!({ "type": kind })).unwrap();
+            assert!(as_kind(&schema).is_some(), "{}", kind);
+            assert!(schema.specification().is_some(), "{}", kind);
+        }
+        let schema = Schema::try_from(json!({"type": "string"})).unwrap();
+        assert!(schema.as_object().is_none());
+    }
+
+    #[test]
+    fn behaves_like_a_subschema() {
+        let subschema = json!({
+            "type": "string",
+            "format": "email",
+            "title": "Contact",
+            "anyOf": [{ "type": "string" }]
+        });
+        let root = Schema::try_from(subschema.clone()).unwrap();
+        let nested = Schema::try_from(json!({
+            "type": "object",
+            "properties": { "contact": subschema }
+        }))
+        .unwrap();
+        assert_eq!(
+            root.as_property(),
+            Some(&nested.properties().unwrap()["contact"])
+        );
+        assert_eq!(root.title(), Some("Contact"));
+
+        let options = ValidationOptions::new().assert_formats(true);
+        assert!(root
+            .validate_with(&json!("ada@example.com"), &options)
+            .is_ok());
+        assert_eq!(
+            root.validate_with(&json!("ada"), &options),
+            nested.validate_with(&json!({"contact": "ada"}), &options)
+        );
+        assert!(root.validate(&json!(1)).is_err());
+    }
+
+    #[test]
+    fn combinators_and_references() {
+        let schema = Schema::try_from(json!({
+            "$ref": "#/definitions/positive",
+            "definitions": {
+                "positive": { "type": "integer", "not": { "type": "string" } }
+            }
+        }))
+        .unwrap();
+        assert!(matches!(schema.as_property(), Some(Property::Ref(_))));
+        assert!(schema.specification().is_none());
+        assert!(schema.validate(&json!(1)).is_ok());
+        assert!(schema.validate(&json!("one")).is_err());
+
+        let schema = Schema::try_from(json!({
+            "oneOf": [{ "type": "integer" }, { "type": "string" }]
+        }))
+        .unwrap();
+        assert!(matches!(schema.as_property(), Some(Property::Any(_))));
+        assert!(schema.validate(&json!(1)).is_ok());
+        assert!(schema.validate(&json!(null)).is_err());
+    }
+
+    #[test]
+    fn booleans() {
+        let schema = Schema::try_from(json!(false)).unwrap();
+        assert_eq!(schema.as_property(), Some(&Property::Boolean(false)));
+        assert!(schema.annotations().is_some());
+    }
+}
+
+mod builder {
+    use serde_json::json;
+    use serde_json_schema::builder::SchemaBuilder;
+    use serde_json_schema::draft::Draft;
+    use serde_json_schema::*;
+
+    #[test]
+    fn matches_the_parsed_document() {
+        let built = Schema::object()
+            .title("User")
+            .property("id", Schema::integer().minimum(1))
+            .property("email", Schema::string().format("email"))
+            .property("tags", Schema::array().items(Schema::string()))
+            .required(["id"])
+            .additional_properties(false)
+            .build();
+        let parsed = Schema::try_from(json!({
+            "type": "object",
+            "title": "User",
+            "properties": {
+                "id": { "type": "integer", "minimum": 1 },
+                "email": { "type": "string", "format": "email" },
+                "tags": { "type": "array", "items": { "type": "string" } }
+            },
+            "required": ["id"],
+            "additionalProperties": false
+        }))
+        .unwrap();
+        assert_eq!(built, parsed);
+        assert_eq!(
+            serde_json::to_value(&built).unwrap(),
+            serde_json::to_value(&parsed).unwrap()
+        );
+
+        assert!(built.validate(&json!({"id": 1, "tags": ["a"]})).is_ok());
+        assert!(built.validate(&json!({"tags": ["a"]})).is_err());
+        assert!(built.validate(&json!({"id": 1, "name": "a"})).is_err());
+    }
+
+    #[test]
+    fn document() {
+        let schema = Schema::reference("#/$defs/positive")
+            .meta_schema(Draft::Draft202012)
+            .id(Url::parse("https://example.com/positive.json").unwrap())
+            .definition("positive", Schema::integer().exclusive_minimum(0))
+            .definition("never", false)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "$id": "https://example.com/positive.json",
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$ref": "#/$defs/positive",
+                "$defs": {
+                    "positive": { "type": "integer", "exclusiveMinimum": 0 },
+                    "never": false
+                }
+            })
+        );
+        assert!(schema.validate(&json!(1)).is_ok());
+        assert!(schema.validate(&json!("1")).is_err());
+
+        assert_eq!(Schema::any().build(), Schema::try_from(json!({})).unwrap());
+        assert_eq!(
+            SchemaBuilder::from(true).build(),
+            Schema::try_from(json!(true)).unwrap()
+        );
+    }
+
+    #[test]
+    fn applicators_and_foreign_keywords() {
+        let schema = Schema::any()
+            .any_of(Schema::string().max_length(3))
+            .any_of(Schema::null())
+            .if_then_else(
+                Schema::string(),
+                Schema::string().min_length(1),
+                Schema::any().required(["never"]),
+            )
+            .keyword("x-internal", true)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "anyOf": [{ "type": "string", "maxLength": 3 }, { "type": "null" }],
+                "if": { "type": "string" },
+                "then": { "type": "string", "minLength": 1 },
+                "else": { "required": ["never"] },
+                "x-internal": true
+            })
+        );
+        assert_eq!(schema.extra_keyword("x-internal"), Some(&json!(true)));
+        assert!(schema.validate(&json!("abc")).is_ok());
+        assert!(schema.validate(&json!(1)).is_err());
+
+        let titled_false = SchemaBuilder::from(false).title("nothing").build();
+        assert_eq!(titled_false.title(), Some("nothing"));
+        assert!(titled_false.validate(&json!(null)).is_err());
+    }
+
+    #[test]
+    fn criteria() {
+        let built = Schema::object()
+            .property("age", Schema::integer().minimum(0).exclusive_maximum(150))
+            .property("price", Schema::number().multiple_of(0.01))
+            .property(
+                "code",
+                Schema::string()
+                    .min_length(2)
+                    .max_length(3)
+                    .pattern("^[A-Z]+$"),
+            )
+            .property("tags", Schema::array().min_items(1).max_items(2))
+            .property("size", Schema::string().enum_values(["S", "M", "L"]))
+            .property("version", Schema::any().const_value(2).maximum(1))
+            .build();
+        let parsed = Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "age": { "type": "integer", "minimum": 0, "exclusiveMaximum": 150 },
+                "price": { "type": "number", "multipleOf": 0.01 },
+                "code": { "type": "string", "minLength": 2, "maxLength": 3, "pattern": "^[A-Z]+$" },
+                "tags": { "type": "array", "items": true, "minItems": 1, "maxItems": 2 },
+                "size": { "type": "string", "enum": ["S", "M", "L"] },
+                "version": { "const": 2, "maximum": 1 }
+            }
+        }))
+        .unwrap();
+        assert_eq!(built, parsed);
+        assert_eq!(
+            serde_json::to_value(&parsed).unwrap()["properties"]["version"],
+            json!({ "const": 2, "maximum": 1 })
+        );
+
+        let valid = json!({"age": 36, "price": 9.99, "code": "AB", "tags": ["a"], "size": "M"});
+        assert!(built.validate(&valid).is_ok());
+        for invalid in [
+            json!({"age": -1}),
+            json!({"age": 150}),
+            json!({"price": 9.999}),
+            json!({"code": "A"}),
+            json!({"code": "ABCD"}),
+            json!({"code": "ab"}),
+            json!({"tags": []}),
+            json!({"tags": [1, 2, 3]}),
+            json!({"size": "XL"}),
+            json!({"version": 1}),
+            json!({"version": 2}),
+        ] {
+            assert!(built.validate(&invalid).is_err(), "{} is valid", invalid);
+        }
+    }
+}
+
+mod editing {
+    use serde_json::json;
+    use serde_json_schema::error::ErrorKind;
+    use serde_json_schema::property::Property;
+    use serde_json_schema::*;
+
+    #[test]
+    fn accessors() {
+        let mut schema = Schema::try_from(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "type": "object",
+            "properties": { "id": { "type": "integer" } }
+        }))
+        .unwrap();
+
+        schema.set_id(Url::parse("https://example.com/user.json").unwrap());
+        schema.set_description("A user");
+        schema
+            .properties_mut()
+            .unwrap()
+            .insert("name".into(), Schema::string().into());
+        schema.required_properties_mut().unwrap().push("id".into());
+        schema.definitions_mut().insert(
+            "positive".into(),
+            Schema::integer().exclusive_minimum(0).into(),
+        );
+        schema
+            .properties_mut()
+            .unwrap()
+            .get_mut("id")
+            .unwrap()
+            .annotations_mut()
+            .deprecated = Some(true);
+
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "$id": "https://example.com/user.json",
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "description": "A user",
+                "properties": {
+                    "id": { "type": "integer", "deprecated": true },
+                    "name": { "type": "string" }
+                },
+                "required": ["id"],
+                "$defs": {
+                    "positive": { "type": "integer", "exclusiveMinimum": 0 }
+                }
+            })
+        );
+        assert!(schema.validate(&json!({"name": "Ada"})).is_err());
+    }
+
+    #[test]
+    fn booleans() {
+        let mut schema = Schema::try_from(json!(false)).unwrap();
+        assert_eq!(schema.as_property_mut(), &mut Property::Boolean(false));
+        assert_eq!(serde_json::to_value(&schema).unwrap(), json!(false));
+
+        schema.set_title("Nothing");
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({ "title": "Nothing", "not": {} })
+        );
+        assert!(schema.validate(&json!(1)).is_err());
+
+        let mut schema = Schema::try_from(json!(true)).unwrap();
+        schema.set_id(Url::parse("https://example.com/any.json").unwrap());
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({ "$id": "https://example.com/any.json" })
+        );
+    }
+
+    #[test]
+    fn pointers() {
+        let mut schema = Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "address": {
+                    "type": "object",
+                    "properties": { "street": { "type": "string" } }
+                }
+            },
+            "anyOf": [{ "required": ["address"] }]
+        }))
+        .unwrap();
+
+        schema
+            .insert("/properties/address/properties/city", Schema::string())
+            .unwrap();
+        schema
+            .insert("/anyOf/0", Schema::any().required(["name"]))
+            .unwrap();
+        schema
+            .insert("/definitions/city", Schema::string())
+            .unwrap();
+        let removed = schema
+            .remove("/properties/address/properties/street")
+            .unwrap();
+        assert_eq!(removed, Property::from(Schema::string()));
+
+        assert_eq!(
+            serde_json::to_value(&schema).unwrap(),
+            json!({
+                "type": "object",
+                "properties": {
+                    "address": {
+                        "type": "object",
+                        "properties": { "city": { "type": "string" } }
+                    }
+                },
+                "anyOf": [{ "required": ["name"] }, { "required": ["address"] }],
+                "definitions": { "city": { "type": "string" } }
+            })
+        );
+
+        for pointer in ["", "properties", "/nothing/here/name", "/anyOf/5"] {
+            let before = schema.clone();
+            let error = schema.insert(pointer, Schema::string()).unwrap_err();
+            assert_eq!(schema, before, "{}", error);
+        }
+        assert!(matches!(
+            schema.remove("/properties/name").unwrap_err().kind(),
+            ErrorKind::InvalidPointer(_)
+        ));
+        assert!(matches!(
+            schema.remove("/type").unwrap_err().kind(),
+            ErrorKind::InvalidPointer(_)
+        ));
+        assert!(schema.remove("/anyOf/1").is_ok());
+    }
+}
+
+mod visit {
+    use serde_json::json;
+    use serde_json_schema::property::{Property, PropertyInstance};
+    use serde_json_schema::visit::{Location, Refs, Visitor};
+    use serde_json_schema::*;
+
+    #[derive(Default)]
+    struct Locations(Vec<(String, Option<&'static str>, usize)>);
+
+    impl Visitor for Locations {
+        fn visit(&mut self, _: &Property, location: &Location) {
+            self.0
+                .push((location.pointer.clone(), location.keyword, location.depth));
+        }
+    }
+
+    fn schema() -> Schema {
+        Schema::try_from(json!({
+            "type": "object",
+            "properties": {
+                "a/b": { "type": "string", "contentSchema": true },
+                "node": { "$ref": "#/definitions/node" }
+            },
+            "anyOf": [{ "required": ["node"] }, false],
+            "definitions": {
+                "node": {
+                    "type": "object",
+                    "properties": { "children": { "type": "array", "items": { "$ref": "#/definitions/node" } } }
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn locations() {
+        let mut locations = Locations::default();
+        schema().walk(&mut locations);
+        let mut expected = vec![
+            ("".into(), None, 0),
+            ("/properties/a~1b".into(), Some("properties"), 1),
+            (
+                "/properties/a~1b/contentSchema".into(),
+                Some("contentSchema"),
+                2,
+            ),
+            ("/properties/node".into(), Some("properties"), 1),
+            ("/anyOf/0".into(), Some("anyOf"), 1),
+            ("/anyOf/1".into(), Some("anyOf"), 1),
+            ("/definitions/node".into(), Some("definitions"), 1),
+            (
+                "/definitions/node/properties/children".into(),
+                Some("properties"),
+                2,
+            ),
+            (
+                "/definitions/node/properties/children/items".into(),
+                Some("items"),
+                3,
+            ),
+        ];
+        // the root comes first, the order of siblings depends on `preserve_order`
+        assert_eq!(locations.0[0], expected[0]);
+        locations.0.sort();
+        expected.sort();
+        assert_eq!(locations.0, expected);
+
+        let mut count = 0;
+        Schema::try_from(json!(true))
+            .unwrap()
+            .walk(&mut |_: &Property, _: &Location| count += 1);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn following_refs() {
+        let mut locations = Locations::default();
+        schema().walk_with(&mut locations, Refs::Follow);
+        let followed: Vec<_> = locations
+            .0
+            .iter()
+            .filter(|(_, keyword, _)| *keyword == Some("$ref"))
+            .collect();
+        // the recursive reference inside of the node is not followed again
+        assert_eq!(
+            followed,
+            vec![
+                &("/definitions/node".into(), Some("$ref"), 2),
+                &("/definitions/node".into(), Some("$ref"), 4),
+            ]
+        );
+        assert_eq!(locations.0.len(), 9 + 3 + 3);
+
+        let mut count = 0;
+        Schema::try_from(json!({ "$ref": "#" }))
+            .unwrap()
+            .walk_with(&mut |_: &Property, _: &Location| count += 1, Refs::Follow);
+        // a reference to itself never reaches a subschema
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn editing() {
+        let mut schema = schema();
+        schema.walk_mut(&mut |property: &mut Property, location: &Location| {
+            if let Property::Value(PropertyInstance::String { .. }) = property {
+                property.annotations_mut().description = Some(location.pointer.clone());
+            }
+            if property.is_false() {
+                *property = Property::Boolean(true);
+            }
+        });
+        assert_eq!(
+            schema.properties().unwrap()["a/b"].description(),
+            Some("/properties/a~1b")
+        );
+        assert!(schema.validate(&json!({})).is_ok());
+
+        let mut schema = Schema::try_from(json!(false)).unwrap();
+        schema.walk_mut(&mut |_: &mut Property, _: &Location| {});
+        assert_eq!(schema, Schema::try_from(json!(false)).unwrap());
+        schema.walk_mut(&mut |property: &mut Property, _: &Location| {
+            property.annotations_mut().title = Some("nothing".into())
+        });
+        assert_eq!(schema.title(), Some("nothing"));
+        assert!(schema.validate(&json!(1)).is_err());
+    }
+}
+
+mod infer {
+    use serde_json::json;
+    use serde_json_schema::*;
+
+    #[test]
+    fn merges_samples() {
+        let samples = [
+            json!({"id": 1, "name": "Ada", "score": 1.5, "tags": ["a"], "manager": null}),
+            json!({"id": 2, "score": 2, "tags": [], "manager": {"id": 1}}),
+        ];
+        let schema = Schema::infer(&samples);
+        let mut required = schema.required_properties().unwrap().clone();
+        required.sort();
+        assert_eq!(required, ["id", "manager", "score", "tags"]);
+
+        let mut inferred = serde_json::to_value(&schema).unwrap();
+        inferred.as_object_mut().unwrap().remove("required");
+        assert_eq!(
+            inferred,
+            json!({
+                "type": "object",
+                "properties": {
+                    "id": { "type": "integer" },
+                    "name": { "type": "string" },
+                    "score": { "type": "number" },
+                    "tags": { "type": "array", "items": { "type": "string" } },
+                    "manager": {
+                        "type": ["object", "null"],
+                        "properties": { "id": { "type": "integer" } },
+                        "required": ["id"]
+                    }
+                }
+            })
+        );
+        for sample in &samples {
+            assert!(schema.validate(sample).is_ok());
+        }
+        assert!(schema.validate(&json!({"id": 1.5})).is_err());
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert_eq!(Schema::infer(&[]), Schema::try_from(json!(true)).unwrap());
+        assert_eq!(
+            Schema::infer(&[json!([])]),
+            Schema::try_from(json!({"type": "array", "items": true})).unwrap()
+        );
+        assert_eq!(
+            Schema::infer(&[json!(1), json!("one"), json!(true)]),
+            Schema::try_from(json!({ "type": ["boolean", "integer", "string"] })).unwrap()
+        );
+    }
+
+    #[test]
+    fn several_types() {
+        let samples = [json!("a"), json!(null), json!("b")];
+        let schema = Schema::infer(&samples);
+        assert_eq!(
+            schema,
+            Schema::try_from(json!({ "type": ["string", "null"] })).unwrap()
+        );
+
+        // an `enum` of the strings would reject the other samples
+        let samples = [json!("a"), json!(null), json!("a"), json!(1)];
+        let options = InferenceOptions::new().enums(2);
+        let schema = Schema::infer_with(&samples, &options);
+        assert_eq!(
+            schema,
+            Schema::try_from(json!({
+                "anyOf": [
+                    { "type": ["integer", "null"] },
+                    { "type": "string", "enum": ["a"] }
+                ]
+            }))
+            .unwrap()
+        );
+        for sample in &samples {
+            assert!(schema.validate(sample).is_ok());
+        }
+        assert!(schema.validate(&json!("b")).is_err());
+    }
+
+    #[test]
+    fn large_integers() {
+        let samples = [json!(u64::MAX), json!(i64::MIN)];
+        let schema = Schema::infer(&samples);
+        assert_eq!(
+            schema,
+            Schema::try_from(json!({"type": "integer"})).unwrap()
+        );
+        for sample in &samples {
+            assert!(schema.validate(sample).is_ok());
+        }
+    }
+
+    #[test]
+    fn formats_and_enums() {
+        let samples: Vec<_> = ["active", "inactive", "active"]
+            .iter()
+            .zip([
+                "2021-01-01T12:00:00Z",
+                "2021-06-01T08:30:00+02:00",
+                "2022-01-01T00:00:00Z",
+            ])
+            .enumerate()
+            .map(|(index, (status, created))| {
+                json!({
+                    "status": status,
+                    "created": created,
+                    "contact": format!("user{}@example.com", index),
+                })
+            })
+            .collect();
+
+        let plain = Schema::infer(&samples);
+        assert_eq!(
+            serde_json::to_value(&plain).unwrap()["properties"]["status"],
+            json!({ "type": "string" })
+        );
+
+        let options = InferenceOptions::new().formats(true).enums(2);
+        let schema = Schema::infer_with(&samples, &options);
+        let properties = &serde_json::to_value(&schema).unwrap()["properties"];
+        assert_eq!(
+            properties["status"],
+            json!({ "type": "string", "enum": ["active", "inactive"] })
+        );
+        assert_eq!(
+            properties["created"],
+            json!({ "type": "string", "format": "date-time" })
+        );
+        // every value differs
+        assert_eq!(
+            properties["contact"],
+            json!({ "type": "string", "format": "email" })
+        );
+    }
+}
+
+mod diff {
+    use serde_json::{json, Value};
+    use serde_json_schema::diff::{Change, ChangeKind, Impact};
+    use serde_json_schema::*;
+
+    fn diff(old: Value, new: Value) -> Vec<Change> {
+        let (old, new) = (
+            Schema::try_from(old).unwrap(),
+            Schema::try_from(new).unwrap(),
+        );
+        schema_diff(&old, &new)
+    }
+
+    fn change(pointer: &str, kind: ChangeKind, impact: Impact) -> Change {
+        Change {
+            pointer: pointer.into(),
+            kind,
+            impact,
+        }
+    }
+
+    #[test]
+    fn properties_and_required() {
+        let old = json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "required": ["name"]
+        });
+        assert!(diff(old.clone(), old.clone()).is_empty());
+
+        let new = json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" }, "age": { "type": "integer" } },
+            "required": ["name", "age"]
+        });
+        let changes = diff(old.clone(), new.clone());
+        assert_eq!(
+            changes,
+            [
+                change(
+                    "/properties/age",
+                    ChangeKind::PropertyAdded("age".into()),
+                    Impact::ForwardCompatible
+                ),
+                change(
+                    "/required",
+                    ChangeKind::BecameRequired("age".into()),
+                    Impact::ForwardCompatible
+                ),
+            ]
+        );
+        assert_eq!(Impact::of(&changes), Impact::ForwardCompatible);
+        assert_eq!(Impact::of(&diff(new, old)), Impact::BackwardCompatible);
+
+        // a closed object did not accept the property before
+        let closed = json!({"type": "object", "additionalProperties": false});
+        let opened = json!({
+            "type": "object",
+            "properties": { "note": { "type": "string" } },
+            "additionalProperties": false
+        });
+        assert_eq!(diff(closed, opened)[0].impact, Impact::BackwardCompatible);
+
+        // naming a property that accepts anything changes nothing
+        let open = json!({"type": "object"});
+        let named = json!({"type": "object", "properties": { "note": true }});
+        assert_eq!(
+            diff(open.clone(), named.clone()),
+            [change(
+                "/properties/note",
+                ChangeKind::PropertyAdded("note".into()),
+                Impact::FullyCompatible
+            )]
+        );
+        assert_eq!(Impact::of(&diff(named, open)), Impact::FullyCompatible);
+    }
+
+    #[test]
+    fn types_and_constraints() {
+        let changes = diff(
+            json!({"type": "integer", "minimum": 0, "maximum": 10}),
+            json!({"type": "number", "minimum": 1, "maximum": 20}),
+        );
+        assert_eq!(
+            changes,
+            [
+                change(
+                    "/type",
+                    ChangeKind::TypeWidened {
+                        old: vec!["integer".into()],
+                        new: vec!["number".into()]
+                    },
+                    Impact::BackwardCompatible
+                ),
+                change(
+                    "/minimum",
+                    ChangeKind::ConstraintTightened {
+                        keyword: "minimum".into(),
+                        old: Some(json!(0)),
+                        new: Some(json!(1))
+                    },
+                    Impact::ForwardCompatible
+                ),
+                change(
+                    "/maximum",
+                    ChangeKind::ConstraintLoosened {
+                        keyword: "maximum".into(),
+                        old: Some(json!(10)),
+                        new: Some(json!(20))
+                    },
+                    Impact::BackwardCompatible
+                ),
+            ]
+        );
+        assert_eq!(Impact::of(&changes), Impact::Breaking);
+
+        let changes = diff(
+            json!({"type": "string", "pattern": "^a", "title": "Old"}),
+            json!({"type": "boolean", "title": "New"}),
+        );
+        let impacts: Vec<_> = changes
+            .iter()
+            .map(|c| (c.pointer.as_str(), c.impact))
+            .collect();
+        assert_eq!(
+            impacts,
+            [
+                ("/type", Impact::Breaking),
+                ("/title", Impact::FullyCompatible),
+                ("/pattern", Impact::BackwardCompatible),
+            ]
+        );
+    }
+
+    #[test]
+    fn nested_enums_and_subschemas() {
+        let changes = diff(
+            json!({
+                "type": "object",
+                "properties": {
+                    "status": { "type": "string", "enum": ["active", "inactive"] },
+                    "tags": { "type": "array", "items": { "type": "string", "maxLength": 5 } }
+                }
+            }),
+            json!({
+                "type": "object",
+                "properties": {
+                    "status": { "type": "string", "enum": ["active", "paused"] },
+                    "tags": { "type": "array", "items": { "type": "string", "maxLength": 3 } }
+                }
+            }),
+        );
+        let status: Vec<_> = changes
+            .iter()
+            .filter(|c| c.pointer == "/properties/status/enum")
+            .map(|c| (&c.kind, c.impact))
+            .collect();
+        assert_eq!(
+            status,
+            [
+                (
+                    &ChangeKind::EnumValueRemoved(json!("inactive")),
+                    Impact::ForwardCompatible
+                ),
+                (
+                    &ChangeKind::EnumValueAdded(json!("paused")),
+                    Impact::BackwardCompatible
+                ),
+            ]
+        );
+        assert!(changes.iter().any(|c| {
+            c.pointer == "/properties/tags/items/maxLength" && c.impact == Impact::ForwardCompatible
+        }));
+
+        // beneath `not` a tighter subschema accepts more
+        let changes = diff(
+            json!({"not": {"type": "string", "minLength": 1}}),
+            json!({"not": {"type": "string", "minLength": 2}}),
+        );
+        assert_eq!(changes[0].pointer, "/not/minLength");
+        assert_eq!(changes[0].impact, Impact::BackwardCompatible);
+
+        let changes = diff(
+            json!({"anyOf": [{"type": "string"}]}),
+            json!({"anyOf": [{"type": "string"}, {"type": "null"}]}),
+        );
+        assert_eq!(
+            changes,
+            [change(
+                "/anyOf",
+                ChangeKind::SubschemasChanged("anyOf".into()),
+                Impact::BackwardCompatible
+            )]
+        );
+
+        let changes = diff(json!({"$ref": "#/$defs/a"}), json!({"$ref": "#/$defs/b"}));
+        assert_eq!(changes[0].impact, Impact::Breaking);
+        assert_eq!(
+            diff(json!(true), json!({"type": "null"}))[0].impact,
+            Impact::ForwardCompatible
+        );
+    }
+
+    #[test]
+    fn untyped_subschemas() {
+        let changes = diff(
+            json!({"properties": {"a": {}}}),
+            json!({"properties": {"a": {}, "b": {"type": "string"}}, "required": ["b"]}),
+        );
+        assert_eq!(
+            changes,
+            [
+                change(
+                    "/properties/b",
+                    ChangeKind::PropertyAdded("b".into()),
+                    Impact::ForwardCompatible
+                ),
+                change(
+                    "/required",
+                    ChangeKind::BecameRequired("b".into()),
+                    Impact::ForwardCompatible
+                ),
+            ]
+        );
+
+        assert_eq!(
+            diff(
+                json!({"type": ["string", "null"]}),
+                json!({"type": ["null", "string"]})
+            ),
+            []
+        );
+        assert_eq!(
+            diff(
+                json!({"type": ["string", "null"]}),
+                json!({"type": "string"})
+            ),
+            [change(
+                "/type",
+                ChangeKind::TypeNarrowed {
+                    old: vec!["null".into(), "string".into()],
+                    new: vec!["string".into()]
+                },
+                Impact::ForwardCompatible
+            )]
+        );
+        assert_eq!(
+            diff(json!({"type": ["string"]}), json!({"type": "string"})),
+            []
+        );
+
+        let changes = diff(
+            json!({"$ref": "#/$defs/a", "properties": {"a": {"type": "string"}}}),
+            json!({"$ref": "#/$defs/a", "properties": {"a": {"type": "integer"}}}),
+        );
+        assert_eq!(changes[0].pointer, "/properties/a/type");
+        assert_eq!(changes[0].impact, Impact::Breaking);
+    }
+
+    #[test]
+    fn patterns_and_dependencies() {
+        let changes = diff(
+            json!({"patternProperties": {"^x-": {"type": "string"}}}),
+            json!({"patternProperties": {"^x-": {"type": "string", "minLength": 1}, "^y-": true}}),
+        );
+        assert_eq!(
+            changes,
+            [
+                change(
+                    "/patternProperties/^x-/minLength",
+                    ChangeKind::ConstraintTightened {
+                        keyword: "minLength".into(),
+                        old: None,
+                        new: Some(json!(1))
+                    },
+                    Impact::ForwardCompatible
+                ),
+                change(
+                    "/patternProperties/^y-",
+                    ChangeKind::PatternPropertyAdded("^y-".into()),
+                    Impact::FullyCompatible
+                ),
+            ]
+        );
+        assert_eq!(
+            diff(
+                json!({"patternProperties": {"^x-": {"type": "string"}}}),
+                json!({"patternProperties": {}, "additionalProperties": false}),
+            )[0]
+            .kind,
+            ChangeKind::PatternPropertyRemoved("^x-".into())
+        );
+
+        let changes = diff(
+            json!({"dependencies": {"a": ["b"], "c": ["d"]}}),
+            json!({"dependencies": {"a": ["b", "e"]}}),
+        );
+        assert_eq!(
+            changes,
+            [
+                change(
+                    "/dependencies/a",
+                    ChangeKind::ConstraintTightened {
+                        keyword: "dependencies".into(),
+                        old: Some(json!(["b"])),
+                        new: Some(json!(["b", "e"]))
+                    },
+                    Impact::ForwardCompatible
+                ),
+                change(
+                    "/dependencies/c",
+                    ChangeKind::ConstraintLoosened {
+                        keyword: "dependencies".into(),
+                        old: Some(json!(["d"])),
+                        new: None
+                    },
+                    Impact::BackwardCompatible
+                ),
+            ]
+        );
+        let changes = diff(
+            json!({"properties": {"a": {"dependentRequired": {"b": ["c"]}}}}),
+            json!({"properties": {"a": {"dependentRequired": {"b": ["c"]}}}}),
+        );
+        assert_eq!(changes, []);
+    }
+
+    #[test]
+    fn positional_items() {
+        let changes = diff(
+            json!({"type": "array", "items": {"type": "string"}}),
+            json!({"type": "array", "items": [{"type": "string"}]}),
+        );
+        assert_eq!(
+            changes,
+            [change(
+                "/additionalItems",
+                ChangeKind::SubschemaReplaced {
+                    old: json!({"type": "string"}),
+                    new: json!(true)
+                },
+                Impact::BackwardCompatible
+            )]
+        );
+
+        let changes = diff(
+            json!({"items": [{"type": "string"}], "additionalItems": false}),
+            json!({"items": [{"type": "string"}, {"type": "integer"}], "additionalItems": false}),
+        );
+        assert_eq!(
+            changes,
+            [change(
+                "/items/1",
+                ChangeKind::SubschemaReplaced {
+                    old: json!(false),
+                    new: json!({"type": "integer"})
+                },
+                Impact::BackwardCompatible
+            )]
+        );
+    }
+}
+
+mod subset {
+    use serde_json::{json, Value};
+    use serde_json_schema::*;
+
+    fn subset(left: Value, right: Value) -> Compatibility {
+        let (left, right) = (
+            Schema::try_from(left).unwrap(),
+            Schema::try_from(right).unwrap(),
+        );
+        left.is_subset_of(&right)
+    }
+
+    #[test]
+    fn types_and_ranges() {
+        use Compatibility::*;
+        assert_eq!(
+            subset(json!({"type": "integer"}), json!({"type": "number"})),
+            Compatible
+        );
+        assert_eq!(
+            subset(json!({"type": "number"}), json!({"type": "integer"})),
+            Incompatible
+        );
+        assert_eq!(subset(json!({"type": "string"}), json!(true)), Compatible);
+        assert_eq!(subset(json!(true), json!({"type": "string"})), Incompatible);
+        assert_eq!(subset(json!(false), json!({"type": "string"})), Compatible);
+        assert_eq!(
+            subset(
+                json!({"type": "integer", "exclusiveMinimum": 0}),
+                json!({"type": "integer", "minimum": 1})
+            ),
+            Compatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "number", "exclusiveMinimum": 0}),
+                json!({"type": "number", "minimum": 0, "maximum": 10})
+            ),
+            Incompatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "string", "minLength": 2, "maxLength": 4}),
+                json!({"type": "string", "maxLength": 8})
+            ),
+            Compatible
+        );
+        // no instances at all
+        assert_eq!(
+            subset(
+                json!({"type": "integer", "minimum": 5, "maximum": 3}),
+                json!({"type": "string"})
+            ),
+            Compatible
+        );
+    }
+
+    #[test]
+    fn enums() {
+        use Compatibility::*;
+        assert_eq!(
+            subset(json!({"enum": ["a", "b"]}), json!({"type": "string"})),
+            Compatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "string", "enum": ["a", "b"]}),
+                json!({"enum": ["a", "c"]})
+            ),
+            Incompatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "boolean"}),
+                json!({"enum": [true, false, null]})
+            ),
+            Compatible
+        );
+        assert_eq!(
+            subset(json!({"type": "string"}), json!({"enum": ["a", "b"]})),
+            Unknown
+        );
+        assert_eq!(
+            subset(json!({"type": "string"}), json!({"enum": [1, 2]})),
+            Incompatible
+        );
+    }
+
+    #[test]
+    fn objects_and_arrays() {
+        use Compatibility::*;
+        let person = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "minLength": 1 },
+                "age": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["name", "age"],
+            "additionalProperties": false
+        });
+        let loose = json!({
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "required": ["name"]
+        });
+        assert_eq!(subset(person.clone(), loose.clone()), Compatible);
+        assert_eq!(subset(loose.clone(), person.clone()), Incompatible);
+
+        let mut closed = loose;
+        closed["additionalProperties"] = json!({"type": "number"});
+        assert_eq!(subset(person, closed), Compatible);
+
+        assert_eq!(
+            subset(
+                json!({"type": "array", "items": {"type": "integer"}, "maxItems": 3}),
+                json!({"type": "array", "items": {"type": "number"}})
+            ),
+            Compatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "array", "items": {"type": "number"}}),
+                json!({"type": "array", "items": {"type": "integer"}})
+            ),
+            Incompatible
+        );
+        // only the empty array, whose elements are all integers
+        assert_eq!(
+            subset(
+                json!({"type": "array", "items": {"type": "number"}, "maxItems": 0}),
+                json!({"type": "array", "items": {"type": "integer"}})
+            ),
+            Compatible
+        );
+        assert_eq!(
+            subset(
+                json!({"items": [{"type": "integer"}, {"type": "string"}], "maxItems": 1}),
+                json!({"items": {"type": "integer"}})
+            ),
+            Compatible
+        );
+
+        // no object has an `id` that is at least 5 and at most 3, so none misses `name`
+        assert_eq!(
+            subset(
+                json!({
+                    "type": "object",
+                    "properties": { "id": { "type": "integer", "minimum": 5, "maximum": 3 } },
+                    "required": ["id"]
+                }),
+                json!({"type": "object", "required": ["id", "name"]})
+            ),
+            Unknown
+        );
+        // the same keywords without `"type": "object"`
+        assert_eq!(
+            subset(
+                json!({"properties": {"id": {"type": "integer"}}, "required": ["id"]}),
+                json!({"properties": {"id": {"type": "number"}}, "required": ["id"]})
+            ),
+            Compatible
+        );
+    }
+
+    #[test]
+    fn references_and_combinators() {
+        use Compatibility::*;
+        assert_eq!(
+            subset(
+                json!({
+                    "$defs": { "id": { "type": "integer", "minimum": 1 } },
+                    "type": "object",
+                    "properties": { "id": { "$ref": "#/$defs/id" } }
+                }),
+                json!({"type": "object", "properties": {"id": {"type": "number"}}})
+            ),
+            Compatible
+        );
+        assert_eq!(
+            subset(
+                json!({"anyOf": [{"type": "string"}, {"type": "null"}]}),
+                json!({"type": ["string", "null"]})
+            ),
+            Compatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "integer"}),
+                json!({"allOf": [{"type": "number"}, {"minimum": 0}]})
+            ),
+            Incompatible
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "integer"}),
+                json!({"anyOf": [{"type": "string"}, {"type": "number"}]})
+            ),
+            Compatible
+        );
+
+        // beyond what is reasoned about
+        assert_eq!(
+            subset(
+                json!({"type": "string"}),
+                json!({"type": "string", "pattern": "^a"})
+            ),
+            Unknown
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "integer"}),
+                json!({"not": {"type": "string"}})
+            ),
+            Unknown
+        );
+        assert_eq!(
+            subset(
+                json!({"type": "string", "pattern": "^a"}),
+                json!({"type": "integer"})
+            ),
+            Unknown
+        );
+    }
+}
+
+mod normalize {
+    use serde_json::{json, Value};
+    use serde_json_schema::*;
+
+    fn normalized(schema: Value) -> Value {
+        let mut schema = Schema::try_from(schema).unwrap();
+        schema.normalize();
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    #[test]
+    fn equivalent_schemas_compare_equal() {
+        let mut a = Schema::try_from(json!({
+            "allOf": [{ "type": ["string"] }],
+            "required": [],
+            "additionalProperties": true,
+            "definitions": { "unused": { "type": "null" } }
+        }))
+        .unwrap();
+        let mut b = Schema::try_from(json!({"type": "string", "minLength": 0})).unwrap();
+        assert_ne!(a, b);
+        a.normalize();
+        b.normalize();
+        assert_eq!(a, b);
+        assert_eq!(serde_json::to_value(&a).unwrap(), json!({"type": "string"}));
+
+        let mut a =
+            Schema::try_from(json!({"type": "object", "required": ["b", "a", "b"]})).unwrap();
+        let mut b = Schema::try_from(json!({"required": ["a", "b"], "type": "object"})).unwrap();
+        a.normalize();
+        b.normalize();
+        assert_eq!(a, b);
+    }
+
+    #[test]
+    fn combinators() {
+        assert_eq!(
+            normalized(json!({
+                "allOf": [
+                    { "allOf": [{ "minimum": 1 }, true] },
+                    { "maximum": 5 }
+                ]
+            })),
+            json!({ "allOf": [{ "maximum": 5 }, { "minimum": 1 }] })
+        );
+        assert_eq!(
+            normalized(json!({"anyOf": [{"type": "string"}, {"type": "string"}]})),
+            json!({"type": "string"})
+        );
+        assert_eq!(
+            normalized(json!({"anyOf": [{"type": "string"}, true]})),
+            json!(true)
+        );
+        assert_eq!(
+            normalized(json!({"allOf": [{"type": "string"}, false]})),
+            json!(false)
+        );
+        // conflicting keywords stay apart
+        let conflicting = json!({"type": "object", "allOf": [{"type": "string"}]});
+        assert_eq!(
+            Schema::try_from(normalized(conflicting.clone())).unwrap(),
+            Schema::try_from(conflicting).unwrap()
+        );
+        assert_eq!(
+            normalized(json!({"properties": {"a": {"if": {"type": "string"}}}})),
+            json!({"properties": {"a": true}})
+        );
+    }
+
+    #[test]
+    fn enums_and_types() {
+        assert_eq!(
+            normalized(json!({"type": "string", "enum": ["b", 1, "a"]})),
+            json!({"enum": ["a", "b"], "type": "string"})
+        );
+        assert_eq!(
+            normalized(json!({"type": "string", "enum": [1]})),
+            json!(false)
+        );
+        assert_eq!(
+            normalized(json!({"type": ["integer", "number", "null"]})),
+            normalized(json!({"type": ["null", "number"]}))
+        );
+        assert_eq!(
+            normalized(json!({"const": "a", "enum": ["a", "b"], "type": "string"})),
+            json!({"const": "a", "type": "string"})
+        );
+    }
+
+    #[test]
+    fn validation_is_unchanged() {
+        let cases = [
+            (
+                json!({"type": "array", "items": true}),
+                vec![json!(5), json!([1])],
+            ),
+            (
+                json!({"type": "integer", "enum": [1, 2]}),
+                vec![json!(1), json!(1.0), json!(3)],
+            ),
+            (
+                json!({"type": "integer", "const": 1}),
+                vec![json!(1), json!(1.0)],
+            ),
+            (
+                json!({
+                    "allOf": [{"properties": {"a": true}, "additionalProperties": true}],
+                    "unevaluatedProperties": false
+                }),
+                vec![json!({"b": 1}), json!({})],
+            ),
+            (
+                json!({
+                    "allOf": [{"items": true}],
+                    "unevaluatedItems": false
+                }),
+                vec![json!([1, 2]), json!([])],
+            ),
+            (
+                json!({
+                    "if": {"properties": {"a": true}, "required": ["a"]},
+                    "unevaluatedProperties": false
+                }),
+                vec![json!({"a": 1}), json!({"b": 1})],
+            ),
+            (
+                json!({
+                    "anyOf": [{"properties": {"a": true}}, true],
+                    "unevaluatedProperties": false
+                }),
+                vec![json!({"a": 1}), json!({"b": 1})],
+            ),
+            (
+                json!({"const": 1, "enum": [1.0, 2]}),
+                vec![json!(1), json!(2)],
+            ),
+        ];
+        for (schema, samples) in cases {
+            let original = Schema::try_from(schema.clone()).unwrap();
+            let mut normal = original.clone();
+            normal.normalize();
+            for sample in samples {
+                assert_eq!(
+                    original.validate(&sample).is_ok(),
+                    normal.validate(&sample).is_ok(),
+                    "{} normalized to {} for {}",
+                    schema,
+                    serde_json::to_value(&normal).unwrap(),
+                    sample
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn definitions() {
+        let schema = json!({
+            "$defs": {
+                "address": { "type": "object", "properties": { "zip": { "$ref": "#/$defs/zip" } } },
+                "zip": { "type": "string" },
+                "unused": { "type": "null" },
+                "identified": { "$anchor": "kept", "type": "null" }
+            },
+            "type": "object",
+            "properties": { "home": { "$ref": "#/$defs/address" } }
+        });
+        let definitions = normalized(schema.clone())["$defs"].clone();
+        let mut names: Vec<_> = definitions.as_object().unwrap().keys().collect();
+        names.sort();
+        assert_eq!(names, ["address", "identified", "zip"]);
+
+        // an anchor could point at any of them
+        let mut anchored = schema;
+        anchored["properties"]["work"] = json!({"$ref": "#kept"});
+        assert_eq!(normalized(anchored)["$defs"].as_object().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn references_with_siblings() {
+        let definitions = json!({ "a": { "type": "string", "allOf": [true] } });
+        for siblings in [
+            json!({"allOf": [false]}),
+            json!({"anyOf": [false]}),
+            json!({"enum": [1], "type": "string"}),
+        ] {
+            // before 2019-09 they are ignored
+            let mut schema = json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "definitions": definitions,
+                "properties": { "b": { "$ref": "#/definitions/a" } }
+            });
+            schema["properties"]["b"]
+                .as_object_mut()
+                .unwrap()
+                .extend(siblings.as_object().unwrap().clone());
+            let normal = normalized(schema.clone());
+            assert_eq!(normal["properties"]["b"], schema["properties"]["b"]);
+            assert_eq!(normal["definitions"]["a"], json!({"type": "string"}));
+
+            // since then they apply
+            let mut schema = json!({
+                "$defs": definitions,
+                "properties": { "b": { "$ref": "#/$defs/a" } }
+            });
+            schema["properties"]["b"]
+                .as_object_mut()
+                .unwrap()
+                .extend(siblings.as_object().unwrap().clone());
+            assert_eq!(normalized(schema)["properties"]["b"], json!(false));
+        }
+    }
+}
+
+mod merge_all_of {
+    use serde_json::{json, Value};
+    use serde_json_schema::error::ErrorKind;
+    use serde_json_schema::*;
+
+    fn merged(schema: Value) -> Value {
+        let mut schema = Schema::try_from(schema).unwrap();
+        schema.merge_all_of().unwrap();
+        serde_json::to_value(&schema).unwrap()
+    }
+
+    fn same(left: Value, right: Value) {
+        assert_eq!(
+            Schema::try_from(left).unwrap(),
+            Schema::try_from(right).unwrap()
+        );
+    }
+
+    #[test]
+    fn inheritance() {
+        let schema = json!({
+            "definitions": {
+                "named": {
+                    "type": "object",
+                    "properties": { "name": { "type": "string", "maxLength": 100 } },
+                    "required": ["name"]
+                }
+            },
+            "allOf": [
+                { "$ref": "#/definitions/named" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "maxLength": 50 },
+                        "age": { "type": "integer", "minimum": 0 }
+                    },
+                    "required": ["age"]
+                }
+            ]
+        });
+        let mut expected = json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string", "maxLength": 50 },
+                "age": { "type": "integer", "minimum": 0 }
+            },
+            "required": ["name", "age"]
+        });
+        expected["definitions"] = schema["definitions"].clone();
+        same(merged(schema), expected);
+    }
+
+    #[test]
+    fn intersections() {
+        same(
+            merged(json!({
+                "allOf": [
+                    { "type": ["number", "string"], "minimum": 0, "maximum": 10 },
+                    { "type": "integer", "minimum": 5, "exclusiveMaximum": 8 },
+                    { "enum": [5, 6, "x"] },
+                    { "enum": [6, 7, "x"] }
+                ]
+            })),
+            json!({
+                "type": "integer",
+                "minimum": 5,
+                "maximum": 10,
+                "exclusiveMaximum": 8,
+                "enum": [6, "x"]
+            }),
+        );
+        // what can't be merged stays in `allOf`
+        same(
+            merged(json!({
+                "type": "string",
+                "allOf": [{ "pattern": "^a" }, { "pattern": "b$" }, { "minLength": 2 }]
+            })),
+            json!({
+                "type": "string",
+                "pattern": "^a",
+                "minLength": 2,
+                "allOf": [{ "pattern": "b$" }]
+            }),
+        );
+        // nested ones are merged as well
+        same(
+            merged(json!({
+                "type": "array",
+                "items": { "allOf": [{ "type": "number" }, { "multipleOf": 2 }, { "multipleOf": 4 }] }
+            })),
+            json!({ "type": "array", "items": { "type": "number", "multipleOf": 4 } }),
+        );
+        same(
+            merged(json!({
+                "allOf": [{ "type": ["integer", "string", "number"] }, { "type": ["integer", "string"] }]
+            })),
+            json!({ "type": ["integer", "string"] }),
+        );
+        // numbers are compared by value
+        same(
+            merged(json!({"allOf": [{ "enum": [1, 2] }, { "enum": [1.0] }]})),
+            json!({ "enum": [1] }),
+        );
+        same(
+            merged(json!({"allOf": [{ "const": 1 }, { "const": 1.0 }]})),
+            json!({ "const": 1 }),
+        );
+        // a boolean `exclusiveMinimum` of draft 4 only applies to the `minimum` next to it
+        let draft4 = json!({
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "allOf": [{ "minimum": 3, "exclusiveMinimum": true }, { "minimum": 5 }]
+        });
+        same(
+            merged(draft4),
+            json!({
+                "$schema": "http://json-schema.org/draft-04/schema#",
+                "minimum": 3,
+                "exclusiveMinimum": true,
+                "allOf": [{ "minimum": 5 }]
+            }),
+        );
+    }
+
+    #[test]
+    fn references() {
+        // keywords next to `$ref` are ignored before 2019-09
+        let draft7 = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "definitions": { "positive": { "type": "integer", "minimum": 1 } },
+            "properties": {
+                "n": { "$ref": "#/definitions/positive", "allOf": [{ "maximum": 5 }] }
+            }
+        });
+        same(merged(draft7.clone()), draft7);
+
+        let modern = json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": { "positive": { "type": "integer", "minimum": 1 } },
+            "properties": {
+                "n": { "$ref": "#/$defs/positive", "allOf": [{ "maximum": 5 }] }
+            }
+        });
+        same(
+            merged(modern)["properties"]["n"].clone(),
+            json!({ "$ref": "#/$defs/positive", "maximum": 5 }),
+        );
+    }
+
+    #[test]
+    fn conflicts() {
+        let conflict = |schema: Value| {
+            let mut schema = Schema::try_from(schema).unwrap();
+            let before = schema.clone();
+            let error = schema.merge_all_of().unwrap_err();
+            assert_eq!(schema, before);
+            match error.kind() {
+                ErrorKind::Conflict { pointer, keyword } => (pointer.clone(), keyword.clone()),
+                other => panic!("unexpected {:?}", other),
+            }
+        };
+        assert_eq!(
+            conflict(json!({"allOf": [{"type": "string"}, {"type": "integer"}]})),
+            ("".to_owned(), "type".to_owned())
+        );
+        assert_eq!(
+            conflict(json!({
+                "properties": {
+                    "n": { "allOf": [{ "type": "integer", "minimum": 5 }, { "maximum": 4 }] }
+                }
+            })),
+            ("/properties/n".to_owned(), "minimum".to_owned())
+        );
+        assert_eq!(
+            conflict(json!({"allOf": [{"enum": [1, 2]}, {"enum": [3]}]})).1,
+            "enum"
+        );
+        // the closed branch forbids what the other one requires
+        assert_eq!(
+            conflict(json!({
+                "allOf": [
+                    { "type": "object", "properties": { "a": true }, "additionalProperties": false },
+                    { "type": "object", "required": ["b"] }
+                ]
+            }))
+            .1,
+            "required"
+        );
+
+        // a property nobody can satisfy is only a conflict if it is required
+        same(
+            merged(json!({
+                "allOf": [
+                    { "type": "object", "properties": { "a": { "type": "string" } } },
+                    { "type": "object", "properties": { "a": { "type": "integer" } } }
+                ]
+            })),
+            json!({ "type": "object", "properties": { "a": false } }),
+        );
+    }
+}
+
+mod satisfiability {
+    use serde_json::{json, Value};
+    use serde_json_schema::satisfiability::{Reason, Unsatisfiable};
+    use serde_json_schema::*;
+
+    fn unsatisfiable(schema: Value) -> Vec<(String, Reason)> {
+        let mut found: Vec<_> = Schema::try_from(schema)
+            .unwrap()
+            .unsatisfiable()
+            .into_iter()
+            .map(|Unsatisfiable { pointer, reason }| (pointer, reason))
+            .collect();
+        found.sort_by(|a, b| a.0.cmp(&b.0));
+        found
+    }
+
+    #[test]
+    fn ranges() {
+        let found = unsatisfiable(json!({
+            "type": "object",
+            "properties": {
+                "exclusive": { "type": "integer", "exclusiveMinimum": 1, "exclusiveMaximum": 2 },
+                "fine": { "type": "number", "exclusiveMinimum": 1, "exclusiveMaximum": 2 },
+                "mixed": { "type": "number", "minimum": 3, "exclusiveMinimum": 1, "exclusiveMaximum": 3 },
+                "name": { "type": "string", "minLength": 5, "maxLength": 3 },
+                "tags": { "type": "array", "minItems": 2, "maxItems": 1 },
+                "untyped": { "minimum": 2, "maximum": 1 }
+            }
+        }));
+        let range = |minimum, maximum| Reason::EmptyRange { minimum, maximum };
+        assert_eq!(
+            found,
+            [
+                (
+                    "/properties/exclusive".into(),
+                    range("exclusiveMinimum", "exclusiveMaximum")
+                ),
+                (
+                    "/properties/mixed".into(),
+                    range("minimum", "exclusiveMaximum")
+                ),
+                ("/properties/name".into(), range("minLength", "maxLength")),
+                ("/properties/tags".into(), range("minItems", "maxItems")),
+            ]
+        );
+    }
+
+    #[test]
+    fn required_enums_and_types() {
+        let found = unsatisfiable(json!({
+            "$defs": {
+                "closed": {
+                    "type": "object",
+                    "properties": { "a": true, "gone": false },
+                    "required": ["a", "b"],
+                    "additionalProperties": false
+                },
+                "patterned": {
+                    "type": "object",
+                    "required": ["x-id"],
+                    "patternProperties": { "^x-": false }
+                },
+                "open": { "type": "object", "required": ["b"] }
+            },
+            "type": "object",
+            "properties": {
+                "empty": { "enum": [] },
+                "mistyped": { "type": "string", "enum": [1, 2] },
+                "constant": { "type": "integer", "const": 1 },
+                "both": { "type": "string", "allOf": [{ "type": "integer" }] },
+                "nullable": { "type": ["string", "null"], "allOf": [{ "type": "null" }] },
+                "referenced": { "type": "string", "allOf": [{ "$ref": "#/$defs/open" }] }
+            }
+        }));
+        assert_eq!(
+            found,
+            [
+                (
+                    "/$defs/closed".into(),
+                    Reason::ForbiddenProperty("b".into())
+                ),
+                (
+                    "/$defs/patterned".into(),
+                    Reason::ForbiddenProperty("x-id".into())
+                ),
+                ("/properties/both".into(), Reason::EmptyTypes),
+                ("/properties/empty".into(), Reason::EmptyEnum),
+                ("/properties/mistyped".into(), Reason::EmptyEnum),
+                ("/properties/referenced".into(), Reason::EmptyTypes),
+            ]
+        );
+
+        // `false` is not a mistake
+        assert!(unsatisfiable(json!({"not": {}, "items": false})).is_empty());
+    }
+
+    #[test]
+    fn untyped_objects() {
+        let found = unsatisfiable(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$defs": { "object": { "type": "object" } },
+            "properties": {
+                "counted": { "type": ["object"], "minProperties": 2, "maxProperties": 1 },
+                "listed": {
+                    "type": ["object"],
+                    "required": ["a"],
+                    "additionalProperties": false
+                },
+                "narrowed": {
+                    "required": ["a"],
+                    "properties": { "a": false },
+                    "allOf": [{ "type": "object" }]
+                },
+                "sibling": {
+                    "$ref": "#/$defs/object",
+                    "type": "object",
+                    "required": ["a"],
+                    "patternProperties": { "^a$": false }
+                },
+                // these only apply to objects
+                "untyped": { "required": ["a"], "additionalProperties": false }
+            }
+        }));
+        assert_eq!(
+            found,
+            [
+                (
+                    "/properties/counted".into(),
+                    Reason::EmptyRange {
+                        minimum: "minProperties",
+                        maximum: "maxProperties"
+                    }
+                ),
+                (
+                    "/properties/listed".into(),
+                    Reason::ForbiddenProperty("a".into())
+                ),
+                (
+                    "/properties/narrowed".into(),
+                    Reason::ForbiddenProperty("a".into())
+                ),
+                (
+                    "/properties/sibling".into(),
+                    Reason::ForbiddenProperty("a".into())
+                ),
+            ]
+        );
+
+        // keywords next to `$ref` are ignored before 2019-09
+        assert!(unsatisfiable(json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "definitions": { "object": { "type": "object" } },
+            "properties": {
+                "sibling": {
+                    "$ref": "#/definitions/object",
+                    "type": "object",
+                    "required": ["a"],
+                    "additionalProperties": false
+                }
+            }
+        }))
+        .is_empty());
+    }
+}
@@ -0,0 +1,179 @@
+//! Runs the cases in `tests/JSON-Schema-Test-Suite`, which follows the layout of the
+//! [official test suite](https://github.com/json-schema-org/JSON-Schema-Test-Suite).
+//!
+//! Cases listed in `tests/known_failures.txt` are expected to fail,
+//! every other case has to pass and every listed case has to keep failing.
+
+use serde::Deserialize;
+use serde_json::Value;
+use url::Url;
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::TryFrom;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde_json_schema::draft::Draft;
+use serde_json_schema::{Schema, ValidationOptions};
+
+const SUITE: &str = "tests/JSON-Schema-Test-Suite";
+const KNOWN_FAILURES: &str = "tests/known_failures.txt";
+
+#[derive(Deserialize)]
+struct Group {
+    description: String,
+    schema: Value,
+    tests: Vec<Case>,
+}
+
+#[derive(Deserialize)]
+struct Case {
+    description: String,
+    data: Value,
+    valid: bool,
+}
+
+/// The schemas of `remotes/`, served as `http://localhost:1234/…` like the upstream suite expects
+fn remotes() -> HashMap<Url, Schema> {
+    let mut remotes = HashMap::new();
+    let root = Path::new(SUITE).join("remotes");
+    for path in json_files(&root) {
+        let relative = path
+            .strip_prefix(&root)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+        let url = Url::parse("http://localhost:1234/")
+            .unwrap()
+            .join(&relative)
+            .unwrap();
+        let schema = Schema::try_from(fs::read_to_string(&path).unwrap()).unwrap();
+        remotes.insert(url, schema);
+    }
+    remotes
+}
+
+fn json_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(json_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+/// Lines of `<draft>/<file> | <group> | <test>`, `#` starts a comment
+fn known_failures(draft: &str) -> BTreeSet<String> {
+    fs::read_to_string(KNOWN_FAILURES)
+        .unwrap()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter(|line| line.starts_with(&format!("{}/", draft)))
+        .map(String::from)
+        .collect()
+}
+
+/// Validates the data of `case`, or explains how the result differs from the expectation
+fn run_case(options: &ValidationOptions, schema: &Value, case: &Case) -> Result<(), String> {
+    let schema = Schema::try_from(schema.clone()).map_err(|e| format!("schema error: {}", e))?;
+    let result = schema.validate_with(&case.data, options);
+    match (result, case.valid) {
+        (Ok(()), true) | (Err(_), false) => Ok(()),
+        (Ok(()), false) => Err("was valid, expected invalid".into()),
+        (Err(errors), true) => Err(format!("expected valid, got {:?}", errors)),
+    }
+}
+
+fn run_draft(draft: &str, version: Draft) {
+    let root = Path::new(SUITE).join("tests").join(draft);
+    assert!(root.is_dir(), "{} is missing", root.display());
+    let known = known_failures(draft);
+    let mut unexpected = Vec::new();
+    let mut failed = BTreeSet::new();
+    let mut total = 0;
+
+    for path in json_files(&root) {
+        let file = path
+            .strip_prefix(&root)
+            .unwrap()
+            .to_string_lossy()
+            .replace('\\', "/");
+        let options = ValidationOptions::new()
+            .draft(version)
+            .resolver(remotes())
+            .assert_formats(file.starts_with("optional/format/"));
+        let groups: Vec<Group> = serde_json::from_str(&fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+
+        for group in &groups {
+            for case in &group.tests {
+                total += 1;
+                let id = format!(
+                    "{}/{} | {} | {}",
+                    draft, file, group.description, case.description
+                );
+                if let Err(reason) = run_case(&options, &group.schema, case) {
+                    if !known.contains(&id) {
+                        unexpected.push(format!("{}\n    {}", id, reason));
+                    }
+                    failed.insert(id);
+                }
+            }
+        }
+    }
+
+    assert!(total > 0, "{} has no cases", root.display());
+    let fixed: Vec<_> = known.difference(&failed).collect();
+    println!(
+        "{}: {}/{} passed ({} known failures)",
+        draft,
+        total - failed.len(),
+        total,
+        known.len()
+    );
+    assert!(
+        unexpected.is_empty(),
+        "unexpected failures:\n{}",
+        unexpected.join("\n")
+    );
+    assert!(
+        fixed.is_empty(),
+        "these now pass, remove them from {}:\n{:#?}",
+        KNOWN_FAILURES,
+        fixed
+    );
+}
+
+#[test]
+fn draft4() {
+    run_draft("draft4", Draft::Draft04);
+}
+
+#[test]
+fn draft6() {
+    run_draft("draft6", Draft::Draft06);
+}
+
+#[test]
+fn draft7() {
+    run_draft("draft7", Draft::Draft07);
+}
+
+#[test]
+fn draft2019_09() {
+    run_draft("draft2019-09", Draft::Draft201909);
+}
+
+#[test]
+fn draft2020_12() {
+    run_draft("draft2020-12", Draft::Draft202012);
+}